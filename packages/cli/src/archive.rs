@@ -0,0 +1,396 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, OptionalExtension};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{calendar, Database, IS_ANSWERED};
+
+pub const FORMAT_VERSION: u32 = 2;
+pub const SCHEMA: &str = include_str!("../schema/archive-v2.schema.json");
+
+// The shape written by `estrange archive`. Version 1 exports (no
+// `format_version`) only carried id, prompt, response and created_at, and
+// the oldest of those have no sync_id - they are matched on their timestamp
+// and prompt instead.
+#[derive(Deserialize)]
+struct Archive {
+    format_version: Option<u32>,
+    entries: Vec<ArchiveEntry>,
+    #[serde(default)]
+    tombstones: Vec<Tombstone>,
+}
+
+// An entry forgotten on the exporting device.
+#[derive(Deserialize)]
+struct Tombstone {
+    sync_id: String,
+    deleted_at: String,
+}
+
+#[derive(Deserialize)]
+struct ArchiveEntry {
+    sync_id: Option<String>,
+    prompt: String,
+    response: Option<String>,
+    created_at: String,
+    modified_at: Option<String>,
+    drawing_vector: Option<Value>,
+    drawing_preview: Option<String>,
+    metadata: Option<Value>,
+}
+
+impl ArchiveEntry {
+    fn label(&self) -> String {
+        match &self.sync_id {
+            Some(sync_id) => format!("{} ({})", sync_id, self.created_at),
+            None => self.created_at.clone(),
+        }
+    }
+
+    // drawing_vector is stored as text; archives carry it as parsed JSON
+    // when possible so the web app can read it directly.
+    fn drawing_vector_text(&self) -> Option<String> {
+        match &self.drawing_vector {
+            None | Some(Value::Null) => None,
+            Some(Value::String(text)) => Some(text.clone()),
+            Some(value) => Some(value.to_string()),
+        }
+    }
+
+    fn metadata_text(&self) -> Result<String> {
+        match &self.metadata {
+            None | Some(Value::Null) => Ok("{}".to_string()),
+            Some(value @ Value::Object(_)) => Ok(value.to_string()),
+            Some(_) => anyhow::bail!("Entry {} has metadata that isn't a JSON object", self.label()),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ImportReport {
+    pub added: usize,
+    pub skipped: usize,
+    pub forgotten: usize,
+    pub conflicts: Vec<String>,
+}
+
+impl Database {
+    pub fn export_all(&self) -> Result<String> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, sync_id, created_at, modified_at, prompt, response,
+                    drawing_vector, drawing_preview, metadata
+             FROM creative_prompts p WHERE p.deleted_at IS NULL AND {} ORDER BY datetime(created_at) ASC",
+            IS_ANSWERED
+        ))?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, Option<String>>(6)?,
+                row.get::<_, Option<String>>(7)?,
+                row.get::<_, Option<String>>(8)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        let mut entries = Vec::with_capacity(rows.len());
+        for (id, sync_id, created_at, modified_at, prompt, response, drawing_vector, drawing_preview, metadata) in rows {
+            let drawing_vector = drawing_vector.map(|text| {
+                serde_json::from_str::<Value>(&text)
+                    .ok()
+                    .filter(Value::is_array)
+                    .unwrap_or(Value::String(text))
+            });
+
+            let metadata: Value = serde_json::from_str(metadata.as_deref().unwrap_or("{}"))
+                .with_context(|| format!("Entry {} has invalid metadata JSON", id))?;
+
+            entries.push(serde_json::json!({
+                "id": id,
+                "sync_id": sync_id,
+                "created_at": created_at,
+                "modified_at": modified_at,
+                "prompt": prompt,
+                "response": response,
+                "drawing_vector": drawing_vector,
+                "drawing_preview": drawing_preview,
+                "metadata": metadata
+            }));
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT sync_id, deleted_at FROM creative_prompts
+             WHERE deleted_at IS NOT NULL ORDER BY datetime(deleted_at) ASC"
+        )?;
+        let tombstones = stmt.query_map([], |row| {
+            Ok(serde_json::json!({
+                "sync_id": row.get::<_, String>(0)?,
+                "deleted_at": row.get::<_, String>(1)?
+            }))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        let export = serde_json::json!({
+            "format_version": FORMAT_VERSION,
+            "export_date": self.calendar.timestamp(),
+            "total_entries": entries.len(),
+            "entries": entries,
+            "tombstones": tombstones
+        });
+
+        validate_archive(&export).context("Refusing to write a lossy export")?;
+
+        Ok(serde_json::to_string_pretty(&export)?)
+    }
+
+    // Import an archive, skipping entries the journal already has. With
+    // `dry_run` everything runs inside a transaction that is rolled back, so
+    // the report is exactly what a real import would do.
+    pub fn import_archive(&self, json: &str, dry_run: bool) -> Result<ImportReport> {
+        let value: Value = serde_json::from_str(json)
+            .context("Failed to parse archive - is this an `estrange archive` export?")?;
+        let mut archive: Archive = serde_json::from_value(value.clone())
+            .context("Failed to parse archive - is this an `estrange archive` export?")?;
+
+        match archive.format_version {
+            Some(version) if version > FORMAT_VERSION => anyhow::bail!(
+                "Archive uses format version {}, but this estrange only reads up to version {}. Upgrade estrange to import it.",
+                version,
+                FORMAT_VERSION
+            ),
+            // Anything `export_all` would refuse to write is refused here too,
+            // before a single row changes
+            Some(FORMAT_VERSION) => validate_archive(&value).context("Refusing to import the archive")?,
+            _ => {}
+        }
+
+        // Archives from before RFC 3339 timestamps carry "... UTC" strings
+        for entry in &mut archive.entries {
+            entry.created_at = calendar::normalize_timestamp(&entry.created_at);
+            entry.modified_at = entry.modified_at.as_deref().map(calendar::normalize_timestamp);
+        }
+        for tombstone in &mut archive.tombstones {
+            tombstone.deleted_at = calendar::normalize_timestamp(&tombstone.deleted_at);
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut report = ImportReport::default();
+
+        for entry in &archive.entries {
+            let existing: Option<(String, Option<String>, bool)> = match &entry.sync_id {
+                Some(sync_id) => tx
+                    .query_row(
+                        "SELECT prompt, response, deleted_at IS NOT NULL FROM creative_prompts WHERE sync_id = ?1",
+                        [sync_id],
+                        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                    )
+                    .optional()?,
+                None => tx
+                    .query_row(
+                        "SELECT prompt, response, deleted_at IS NOT NULL FROM creative_prompts
+                         WHERE datetime(created_at) = datetime(?1) AND prompt = ?2",
+                        params![entry.created_at, entry.prompt],
+                        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                    )
+                    .optional()?,
+            };
+
+            match existing {
+                // Forgotten here - importing must not resurrect it
+                Some((_, _, true)) => report.skipped += 1,
+                Some((prompt, response, _)) if prompt == entry.prompt && response == entry.response => {
+                    report.skipped += 1;
+                }
+                Some(_) => report.conflicts.push(entry.label()),
+                None => {
+                    tx.execute(
+                        "INSERT INTO creative_prompts
+                            (sync_id, prompt, response, created_at, modified_at,
+                             drawing_vector, drawing_preview, metadata)
+                         VALUES (COALESCE(?1, lower(hex(randomblob(16)))), ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                        params![
+                            entry.sync_id,
+                            entry.prompt,
+                            entry.response,
+                            entry.created_at,
+                            entry.modified_at.as_deref().unwrap_or(&entry.created_at),
+                            entry.drawing_vector_text(),
+                            entry.drawing_preview,
+                            entry.metadata_text()?,
+                        ],
+                    )?;
+                    report.added += 1;
+                }
+            }
+        }
+
+        for tombstone in &archive.tombstones {
+            let forgotten = tx.execute(
+                "UPDATE creative_prompts SET deleted_at = ?1, modified_at = ?1
+                 WHERE sync_id = ?2 AND deleted_at IS NULL",
+                params![tombstone.deleted_at, tombstone.sync_id],
+            )?;
+
+            if forgotten > 0 {
+                report.forgotten += 1;
+            } else {
+                // Keep the tombstone even for entries this journal never
+                // had, so a later import of an older archive can't revive it.
+                tx.execute(
+                    "INSERT OR IGNORE INTO creative_prompts (sync_id, prompt, created_at, modified_at, deleted_at)
+                     VALUES (?1, '', ?2, ?2, ?2)",
+                    params![tombstone.sync_id, tombstone.deleted_at],
+                )?;
+            }
+        }
+
+        if dry_run {
+            tx.rollback()?;
+        } else {
+            tx.commit()?;
+        }
+
+        Ok(report)
+    }
+}
+
+fn validate_archive(archive: &Value) -> Result<()> {
+    let schema: Value = serde_json::from_str(SCHEMA).context("Bundled archive schema is not valid JSON")?;
+    let validator = jsonschema::validator_for(&schema)
+        .map_err(|e| anyhow::anyhow!("Bundled archive schema is invalid: {}", e))?;
+
+    let errors: Vec<String> = validator
+        .iter_errors(archive)
+        .map(|e| format!("{} at {}", e, e.instance_path))
+        .collect();
+
+    if !errors.is_empty() {
+        anyhow::bail!(
+            "Archive failed schema validation:\n  {}",
+            errors.join("\n  ")
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::Calendar;
+    use crate::Config;
+
+    fn journal(dir: &tempfile::TempDir, name: &str) -> Database {
+        let calendar = Calendar::from_config(&Config::default()).unwrap();
+        Database::open(&dir.path().join(name), calendar).unwrap()
+    }
+
+    fn row(db: &Database, sync_id: &str) -> (String, String, Option<String>, Option<String>) {
+        db.conn
+            .query_row(
+                "SELECT modified_at, metadata, drawing_vector, deleted_at FROM creative_prompts WHERE sync_id = ?1",
+                [sync_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap()
+    }
+
+    const KEPT: &str = "0123456789abcdef0123456789abcdef";
+    const FORGOTTEN: &str = "fedcba9876543210fedcba9876543210";
+
+    #[test]
+    fn round_trips_every_column_and_tombstones() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = journal(&dir, "source.db");
+        source
+            .conn
+            .execute_batch(&format!(
+                "INSERT INTO creative_prompts (sync_id, created_at, modified_at, prompt, response, drawing_vector, metadata)
+                 VALUES ('{KEPT}', '2026-01-31T18:04:00Z', '2026-02-02T09:00:00Z', 'A kettle that boils memories',
+                         'It sang.', '[[0,0],[4,2]]', '{{\"tags\":[\"kitchen\"],\"source\":\"deck\"}}');
+                 INSERT INTO creative_prompts (sync_id, created_at, modified_at, prompt, response)
+                 VALUES ('{FORGOTTEN}', '2026-02-01T18:04:00Z', '2026-02-01T18:04:00Z', 'Gravity works sideways', 'Oof.');"
+            ))
+            .unwrap();
+        let forgotten_id: i64 = source
+            .conn
+            .query_row("SELECT id FROM creative_prompts WHERE sync_id = ?1", [FORGOTTEN], |row| row.get(0))
+            .unwrap();
+        source.forget_entry(forgotten_id).unwrap();
+
+        let archive = source.export_all().unwrap();
+        let target = journal(&dir, "target.db");
+
+        // A dry run reports the import without writing it
+        let report = target.import_archive(&archive, true).unwrap();
+        assert_eq!(report.added, 1);
+        let count: i64 = target.conn.query_row("SELECT COUNT(*) FROM creative_prompts", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 0);
+
+        let report = target.import_archive(&archive, false).unwrap();
+        assert_eq!(report.added, 1);
+        assert!(report.conflicts.is_empty());
+
+        let (modified_at, metadata, drawing_vector, deleted_at) = row(&target, KEPT);
+        assert_eq!(modified_at, "2026-02-02T09:00:00Z");
+        assert_eq!(
+            serde_json::from_str::<Value>(&metadata).unwrap(),
+            serde_json::json!({"tags": ["kitchen"], "source": "deck"})
+        );
+        assert_eq!(
+            serde_json::from_str::<Value>(&drawing_vector.unwrap()).unwrap(),
+            serde_json::json!([[0, 0], [4, 2]])
+        );
+        assert_eq!(deleted_at, None);
+
+        // The forgotten entry arrives as its tombstone only
+        let (_, _, _, deleted_at) = row(&target, FORGOTTEN);
+        assert!(deleted_at.is_some());
+
+        // Importing again changes nothing
+        let report = target.import_archive(&archive, false).unwrap();
+        assert_eq!((report.added, report.skipped), (0, 1));
+    }
+
+    #[test]
+    fn imports_version_1_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = journal(&dir, "estrange.db");
+        let archive = r#"{"entries": [
+            {"id": 3, "prompt": "A doorknob that remembers", "response": "Every hand.", "created_at": "2025-01-31 18:04:00 UTC"}
+        ]}"#;
+
+        let report = db.import_archive(archive, false).unwrap();
+        assert_eq!(report.added, 1);
+
+        let (created_at, modified_at): (String, String) = db
+            .conn
+            .query_row("SELECT created_at, modified_at FROM creative_prompts", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!(created_at, "2025-01-31T18:04:00Z");
+        assert_eq!(modified_at, created_at);
+
+        // Without a sync_id, the timestamp and prompt identify it
+        let report = db.import_archive(archive, false).unwrap();
+        assert_eq!((report.added, report.skipped), (0, 1));
+    }
+
+    #[test]
+    fn refuses_invalid_version_2_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = journal(&dir, "estrange.db");
+        let archive = r#"{"format_version": 2, "export_date": "2026-01-01T00:00:00Z", "total_entries": 1, "entries": [
+            {"id": 1, "sync_id": "ABC-not-hex", "created_at": "2026-01-01T00:00:00Z", "modified_at": "2026-01-01T00:00:00Z",
+             "prompt": "p", "response": "r", "drawing_vector": null, "drawing_preview": null, "metadata": {}}
+        ]}"#;
+
+        assert!(db.import_archive(archive, false).is_err());
+        let count: i64 = db.conn.query_row("SELECT COUNT(*) FROM creative_prompts", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 0);
+    }
+}
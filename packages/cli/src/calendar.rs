@@ -0,0 +1,171 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+use chrono_tz::Tz;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::Connection;
+
+use crate::Config;
+
+// Which day an entry belongs to. Days follow the configured IANA timezone
+// (the system's local zone when none is set) and start at `day_starts_at`,
+// so with `day_starts_at = 4` a 1am entry still counts for the day before.
+#[derive(Clone)]
+pub struct Calendar {
+    timezone: Option<Tz>,
+    day_starts_at: u32,
+}
+
+impl Calendar {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let timezone = config
+            .timezone
+            .as_deref()
+            .map(|name| {
+                name.parse::<Tz>()
+                    .map_err(|_| anyhow::anyhow!("Unknown timezone '{}' in config - use an IANA name like America/Los_Angeles", name))
+            })
+            .transpose()?;
+
+        let day_starts_at = config.day_starts_at.unwrap_or(0);
+        if day_starts_at > 23 {
+            anyhow::bail!("day_starts_at must be an hour from 0 to 23, got {}", day_starts_at);
+        }
+
+        Ok(Calendar { timezone, day_starts_at })
+    }
+
+    fn localize(&self, time: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self.timezone {
+            Some(tz) => time.with_timezone(&tz).fixed_offset(),
+            None => time.with_timezone(&Local).fixed_offset(),
+        }
+    }
+
+    pub fn now(&self) -> DateTime<FixedOffset> {
+        self.localize(Utc::now())
+    }
+
+    // The current time as stored in the database: RFC 3339 with the offset
+    // in effect where the entry was written.
+    pub fn timestamp(&self) -> String {
+        self.now().to_rfc3339_opts(SecondsFormat::Secs, false)
+    }
+
+    pub fn journal_day(&self, time: DateTime<Utc>) -> NaiveDate {
+        (self.localize(time) - Duration::hours(self.day_starts_at as i64)).date_naive()
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.journal_day(Utc::now())
+    }
+}
+
+// Read a stored timestamp. Besides RFC 3339, this accepts the formats older
+// versions wrote - "2025-01-31 18:04:00 UTC" and SQLite's CURRENT_TIMESTAMP -
+// both of which are UTC.
+pub fn parse_timestamp(text: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Some(time.with_timezone(&Utc));
+    }
+
+    let naive = text.trim().trim_end_matches(" UTC");
+    NaiveDateTime::parse_from_str(naive, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(naive, "%Y-%m-%dT%H:%M:%S"))
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+// Rewrite a timestamp from an older archive in RFC 3339, leaving anything
+// unrecognised untouched.
+pub fn normalize_timestamp(text: &str) -> String {
+    match parse_timestamp(text) {
+        Some(time) if DateTime::parse_from_rfc3339(text).is_err() => time.to_rfc3339_opts(SecondsFormat::Secs, true),
+        _ => text.to_string(),
+    }
+}
+
+// `journal_day(created_at)` gives the YYYY-MM-DD day an entry counts for,
+// so day filters and streaks bucket entries the same way `estrange` does.
+pub fn register_functions(conn: &Connection, calendar: &Calendar) -> Result<()> {
+    let calendar = calendar.clone();
+    conn.create_scalar_function(
+        "journal_day",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        move |ctx| {
+            let text = ctx.get::<Option<String>>(0)?;
+            Ok(text
+                .as_deref()
+                .and_then(parse_timestamp)
+                .map(|time| calendar.journal_day(time).format("%Y-%m-%d").to_string()))
+        },
+    )
+    .context("Failed to register journal_day")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calendar(timezone: &str, day_starts_at: u32) -> Calendar {
+        Calendar {
+            timezone: Some(timezone.parse().unwrap()),
+            day_starts_at,
+        }
+    }
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        parse_timestamp(text).unwrap()
+    }
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn small_hours_count_for_the_day_before() {
+        let calendar = calendar("UTC", 4);
+        assert_eq!(calendar.journal_day(utc("2026-03-10T01:00:00Z")), date("2026-03-09"));
+        assert_eq!(calendar.journal_day(utc("2026-03-10T04:00:00Z")), date("2026-03-10"));
+    }
+
+    #[test]
+    fn days_follow_the_configured_timezone() {
+        // UTC-8 in winter: 22:30 local on the 9th is already the 10th in UTC
+        let calendar = calendar("America/Los_Angeles", 0);
+        assert_eq!(calendar.journal_day(utc("2026-01-10T06:30:00Z")), date("2026-01-09"));
+        assert_eq!(calendar.journal_day(utc("2026-01-10T08:00:00Z")), date("2026-01-10"));
+    }
+
+    #[test]
+    fn day_start_applies_in_local_time() {
+        // 02:00 on the 10th in Los Angeles, with days starting at 4am
+        let calendar = calendar("America/Los_Angeles", 4);
+        assert_eq!(calendar.journal_day(utc("2026-01-10T10:00:00Z")), date("2026-01-09"));
+    }
+
+    #[test]
+    fn rejects_unusable_settings() {
+        let config = Config {
+            timezone: Some("Mars/Olympus_Mons".to_string()),
+            ..Config::default()
+        };
+        assert!(Calendar::from_config(&config).is_err());
+
+        let config = Config {
+            day_starts_at: Some(24),
+            ..Config::default()
+        };
+        assert!(Calendar::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn reads_older_timestamp_formats() {
+        let expected = utc("2025-01-31T18:04:00Z");
+        assert_eq!(parse_timestamp("2025-01-31 18:04:00 UTC"), Some(expected));
+        assert_eq!(parse_timestamp("2025-01-31 18:04:00"), Some(expected));
+        assert_eq!(normalize_timestamp("2025-01-31 18:04:00 UTC"), "2025-01-31T18:04:00Z");
+        assert_eq!(normalize_timestamp("yesterday"), "yesterday");
+    }
+}
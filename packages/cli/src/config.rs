@@ -0,0 +1,306 @@
+use anyhow::{Context, Result};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Table};
+
+use crate::calendar::Calendar;
+use crate::http::HttpClient;
+use crate::{generators, similarity, Config};
+
+const EXAMPLE: &str = include_str!("../config.example.toml");
+pub const ENV_VAR: &str = "ESTRANGE_CONFIG";
+
+// Where the config lives by default: $XDG_CONFIG_HOME/estrange/config.toml
+// (~/.config on Linux, ~/Library/Application Support on macOS).
+pub fn default_path() -> Result<PathBuf> {
+    let mut path = dirs::config_dir()
+        .or_else(dirs::home_dir)
+        .context("Could not find a directory for the config file")?;
+    path.push("estrange");
+    path.push("config.toml");
+    Ok(path)
+}
+
+// Before config files had a home, estrange read config.toml from wherever
+// it was run. That copy is still read when the default file doesn't exist.
+fn legacy_path() -> Option<PathBuf> {
+    let path = env::current_dir().ok()?.join("config.toml");
+    path.exists().then_some(path)
+}
+
+// One file in the stack that makes up the config
+pub struct Layer {
+    pub source: &'static str,
+    pub path: PathBuf,
+}
+
+// The files to read, lowest priority first: the default file (or the legacy
+// one in the working directory), then $ESTRANGE_CONFIG, then `--config`.
+// Later files override single keys of earlier ones.
+pub fn layers(explicit: Option<&Path>) -> Result<Vec<Layer>> {
+    let mut layers = Vec::new();
+
+    let default = default_path()?;
+    match legacy_path() {
+        Some(legacy) if !default.exists() => layers.push(Layer { source: "working directory", path: legacy }),
+        _ => layers.push(Layer { source: "default", path: default }),
+    }
+    if let Some(path) = env::var_os(ENV_VAR).filter(|path| !path.is_empty()) {
+        layers.push(Layer { source: ENV_VAR, path: PathBuf::from(path) });
+    }
+    if let Some(path) = explicit {
+        layers.push(Layer { source: "--config", path: path.to_path_buf() });
+    }
+
+    Ok(layers)
+}
+
+// The file `config set` and `config init` write to: the highest-priority layer.
+pub fn write_target(explicit: Option<&Path>) -> Result<PathBuf> {
+    let layer = layers(explicit)?.pop().context("No config file to write to")?;
+    Ok(layer.path)
+}
+
+fn read_table(path: &Path) -> Result<toml::Table> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read config file at {}", path.display()))?;
+    toml::from_str(&content)
+        .with_context(|| format!("Failed to parse config file at {} - ensure it's valid TOML", path.display()))
+}
+
+// Tables merge key by key; anything else in `over` replaces what's in `base`.
+fn merge(base: &mut toml::Table, over: toml::Table) {
+    for (key, value) in over {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(over_table)) => merge(base_table, over_table),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+// Every layer merged into one table. The default file may be missing; a
+// file named by $ESTRANGE_CONFIG or `--config` must exist.
+pub fn merged_table(explicit: Option<&Path>) -> Result<toml::Table> {
+    let mut merged = toml::Table::new();
+
+    for layer in layers(explicit)? {
+        if !layer.path.exists() {
+            if layer.source == "default" {
+                continue;
+            }
+            anyhow::bail!("Config file from {} not found: {}", layer.source, layer.path.display());
+        }
+        if layer.source == "working directory" {
+            eprintln!(
+                "Note: Reading config.toml from the current directory. Move it to {} so estrange finds it from anywhere.",
+                default_path()?.display()
+            );
+        }
+        merge(&mut merged, read_table(&layer.path)?);
+    }
+
+    Ok(merged)
+}
+
+impl Config {
+    pub fn load(explicit: Option<&Path>) -> Result<Self> {
+        let table = merged_table(explicit)?;
+        Config::from_table(table)
+    }
+
+    // Settings that parse but can't be used: unknown timezones, temperatures
+    // out of range, a default generator that doesn't exist...
+    pub fn validate(&self) -> Result<()> {
+        Calendar::from_config(self)?;
+        if let Some(generation) = &self.generation {
+            generation.validate()?;
+        }
+        similarity::threshold(self.similarity_threshold)?;
+        generators::resolve(self, None)?;
+        HttpClient::new(self.http.as_ref())?;
+        if let Some(editor) = &self.editor {
+            editor.validate()?;
+        }
+        Ok(())
+    }
+
+    fn from_table(table: toml::Table) -> Result<Self> {
+        toml::Value::Table(table)
+            .try_into()
+            .context("Config doesn't match the expected settings - see config.example.toml")
+    }
+}
+
+// Write the example config to the file asked for by name, else the default
+// location (not the legacy one, so this is how to move off it).
+pub fn init(explicit: Option<&Path>, force: bool) -> Result<PathBuf> {
+    let path = match explicit.map(Path::to_path_buf).or_else(|| env::var_os(ENV_VAR).map(PathBuf::from)) {
+        Some(path) => path,
+        None => default_path()?,
+    };
+    if path.exists() && !force {
+        anyhow::bail!("{} already exists - pass --force to replace it", path.display());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, EXAMPLE)?;
+    Ok(path)
+}
+
+// The effective value of a dotted key, e.g. `metrics.grafana_user`.
+pub fn get(explicit: Option<&Path>, key: &str) -> Result<String> {
+    let merged = toml::Value::Table(merged_table(explicit)?);
+    let value = key
+        .split('.')
+        .try_fold(&merged, |value, part| value.get(part))
+        .with_context(|| format!("{} is not set", key))?;
+
+    Ok(match value {
+        toml::Value::String(text) => text.clone(),
+        toml::Value::Table(table) => toml::to_string_pretty(table)?.trim_end().to_string(),
+        other => other.to_string(),
+    })
+}
+
+// Set a dotted key in the write target, keeping its comments and layout.
+// Values are read as TOML (`0.9`, `true`, `["a", "b"]`), falling back to a
+// plain string. The result must still be a valid config, or nothing is written.
+pub fn set(explicit: Option<&Path>, key: &str, raw: &str) -> Result<PathBuf> {
+    let path = write_target(explicit)?;
+    let content = if path.exists() { fs::read_to_string(&path)? } else { String::new() };
+    let parse = |content: &str| -> Result<DocumentMut> {
+        content
+            .parse()
+            .with_context(|| format!("Failed to parse config file at {} - ensure it's valid TOML", path.display()))
+    };
+    let original = parse(&content)?;
+
+    let parts: Vec<&str> = key.split('.').collect();
+    if parts.iter().any(|part| part.trim().is_empty()) {
+        anyhow::bail!("'{}' isn't a valid key", key);
+    }
+
+    // A value that parses as some other TOML type may still be meant as a
+    // string (an all-digit key, say) - try that before giving up.
+    let candidates = match raw.parse::<toml_edit::Value>() {
+        Ok(value) if !value.is_str() => vec![value, toml_edit::Value::from(raw)],
+        _ => vec![toml_edit::Value::from(raw)],
+    };
+
+    let mut first_error = None;
+    for value in candidates {
+        let mut document = match uncomment_example(&content, &original, &parts, &value) {
+            Some(uncommented) => parse(&uncommented)?,
+            None => original.clone(),
+        };
+        set_path(document.as_table_mut(), &parts, value)?;
+        match check(&document, &path, explicit) {
+            Ok(()) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&path, document.to_string())?;
+                return Ok(path);
+            }
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    Err(first_error.context("No value to set")?)
+        .with_context(|| format!("Refusing to set {} - the config would no longer load", key))
+}
+
+// Most settings in a file from `config init` are commented-out examples,
+// like `# timezone = "America/Los_Angeles"`. Setting one uncomments it (and
+// its `# [table]` header) where it is, beside its explanation. None when
+// there's no such example, or the setting already exists.
+fn uncomment_example(content: &str, document: &DocumentMut, parts: &[&str], value: &toml_edit::Value) -> Option<String> {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let is_header = |line: &str| line.starts_with('[') || line.starts_with("# [");
+
+    let (key, range) = match parts {
+        [key] => {
+            if document.contains_key(key) {
+                return None;
+            }
+            // Top-level examples come before the first table
+            (*key, 0..lines.iter().position(|line| is_header(line)).unwrap_or(lines.len()))
+        }
+        [table, key] => {
+            if document.contains_key(table) {
+                return None;
+            }
+            let header = lines.iter().position(|line| line.trim() == format!("# [{}]", table))?;
+            let end = lines[header..]
+                .iter()
+                .position(|line| line.trim().is_empty())
+                .map_or(lines.len(), |offset| header + offset);
+            lines[header] = format!("[{}]", table);
+            (*key, header + 1..end)
+        }
+        _ => return None,
+    };
+
+    let example = format!("# {} =", key);
+    let index = range.into_iter().find(|&i| lines[i].starts_with(&example))?;
+    lines[index] = format!("{} = {}", key, value.to_string().trim());
+    Some(lines.join("\n") + "\n")
+}
+
+fn set_path(table: &mut Table, parts: &[&str], value: toml_edit::Value) -> Result<()> {
+    let (last, parents) = parts.split_last().context("Empty key")?;
+
+    let mut current = table;
+    for part in parents {
+        let entry = current.entry(part).or_insert_with(|| {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            Item::Table(table)
+        });
+        current = entry
+            .as_table_mut()
+            .with_context(|| format!("'{}' isn't a table, so it can't hold '{}'", part, parts.join(".")))?;
+    }
+
+    // Keep any comment that decorated the old value
+    match current.get_mut(last).and_then(Item::as_value_mut) {
+        Some(existing) => {
+            let decor = existing.decor().clone();
+            *existing = value;
+            *existing.decor_mut() = decor;
+        }
+        None => {
+            current.insert(last, toml_edit::value(value));
+        }
+    }
+    Ok(())
+}
+
+// Would the layers still load with `document` in place of the file at `path`?
+fn check(document: &DocumentMut, path: &Path, explicit: Option<&Path>) -> Result<()> {
+    let mut merged = toml::Table::new();
+    for layer in layers(explicit)? {
+        if layer.path == path {
+            merge(&mut merged, toml::from_str(&document.to_string())?);
+        } else if layer.path.exists() {
+            merge(&mut merged, read_table(&layer.path)?);
+        }
+    }
+    Config::from_table(merged)?.validate()
+}
+
+pub fn print_paths(explicit: Option<&Path>) -> Result<()> {
+    println!("⚙️  Config is read from (later files override earlier ones):");
+    for layer in layers(explicit)? {
+        let status = if layer.path.exists() { "" } else { " (not found)" };
+        println!("   {:<18} {}{}", layer.source, layer.path.display(), status);
+    }
+    println!("\n✏️  'estrange config set' writes to {}", write_target(explicit)?.display());
+    Ok(())
+}
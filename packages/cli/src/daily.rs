@@ -0,0 +1,122 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::get_db_path;
+use crate::http::HttpClient;
+use crate::providers::{Prompt, PromptProvider, PromptRequest};
+
+// Where the prompt generator worker publishes, and the web app reads, the
+// prompt of the day
+pub const DEFAULT_URL: &str = "https://estrange-prompt-server.kalley-powell.workers.dev/prompt";
+
+// The shared prompt of the day, as the prompt generator worker
+// (packages/prompt-generator) publishes it and the web app shows it.
+#[derive(Serialize, Deserialize)]
+struct PromptData {
+    prompt: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    generated_at: Option<String>,
+    id: String,
+    #[serde(default)]
+    version: Option<String>,
+}
+
+// The last prompt fetched, and the journal day it was fetched for.
+#[derive(Serialize, Deserialize)]
+struct CachedPrompt {
+    day: NaiveDate,
+    data: PromptData,
+}
+
+pub struct DailyPromptClient {
+    http: HttpClient,
+    url: String,
+}
+
+impl DailyPromptClient {
+    pub fn new(http: HttpClient, url: Option<String>) -> Self {
+        DailyPromptClient {
+            http,
+            url: url.unwrap_or_else(|| DEFAULT_URL.to_string()),
+        }
+    }
+
+    fn cache_path() -> Result<PathBuf> {
+        let mut path = get_db_path()?;
+        path.set_file_name("daily-prompt.json");
+        Ok(path)
+    }
+
+    fn read_cache(day: NaiveDate) -> Option<PromptData> {
+        let content = fs::read_to_string(Self::cache_path().ok()?).ok()?;
+        let cached: CachedPrompt = serde_json::from_str(&content).ok()?;
+        (cached.day == day).then_some(cached.data)
+    }
+
+    fn write_cache(cached: &CachedPrompt) -> Result<()> {
+        fs::write(Self::cache_path()?, serde_json::to_string_pretty(cached)?)?;
+        Ok(())
+    }
+
+    async fn fetch(&self, url: &str) -> Result<PromptData> {
+        let response = self
+            .http
+            .send(self.http.get(url))
+            .await
+            .with_context(|| format!("Failed to fetch the daily prompt from {}", url))?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Daily prompt endpoint returned {}", response.status());
+        }
+
+        response
+            .json()
+            .await
+            .context("Failed to parse the daily prompt - expected the worker's PromptData JSON")
+    }
+}
+
+#[async_trait]
+impl PromptProvider for DailyPromptClient {
+    fn name(&self) -> String {
+        format!("daily prompt ({})", self.url)
+    }
+
+    // Fetched at most once per journal day; the cached copy keeps the CLI
+    // on the same prompt even if the worker publishes again later.
+    async fn generate(&self, request: &PromptRequest) -> Result<Prompt> {
+        let data = match Self::read_cache(request.day) {
+            Some(data) => data,
+            None => {
+                let cached = CachedPrompt {
+                    day: request.day,
+                    data: self.fetch(&self.url).await?,
+                };
+                if let Err(e) = Self::write_cache(&cached) {
+                    eprintln!("Warning: Failed to cache the daily prompt: {}", e);
+                }
+                cached.data
+            }
+        };
+
+        let mut prompt = Prompt::new(data.prompt.trim(), "daily")
+            .with("prompt_id", data.id)
+            .with("prompt_tags", data.tags);
+        if let Some(version) = data.version {
+            prompt = prompt.with("prompt_version", version);
+        }
+        Ok(prompt)
+    }
+
+    // Fetches without touching the cache
+    async fn check(&self) -> Result<String> {
+        let data = self.fetch(&self.url).await?;
+        Ok(format!("today's prompt is {}", data.id))
+    }
+}
@@ -0,0 +1,353 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::providers::{Prompt, PromptProvider, PromptRequest};
+use crate::rng::stable_hash;
+use crate::{get_db_path, tags, Database};
+
+const BUNDLED: &str = include_str!("../decks/default.toml");
+const DEFAULT_USER_DECK: &str = "mine";
+
+// A deck file, in TOML or JSON:
+//
+//   name = "my deck"
+//   [[prompts]]
+//   text = "A staircase that only goes down"
+//   tags = ["place"]
+//
+// Plain text files with one prompt per line are read too.
+#[derive(Serialize, Deserialize, Default)]
+pub struct DeckFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub prompts: Vec<Card>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Card {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    // Name of the deck the card was loaded from
+    #[serde(skip)]
+    pub deck: String,
+}
+
+pub struct LoadedDeck {
+    pub name: String,
+    // None for the bundled deck
+    pub path: Option<PathBuf>,
+    pub cards: Vec<Card>,
+}
+
+pub fn decks_dir() -> Result<PathBuf> {
+    let mut path = get_db_path()?;
+    path.set_file_name("decks");
+    Ok(path)
+}
+
+// Where the deck called `name` is kept. Names are file names, so they can't
+// reach outside the decks directory.
+fn deck_path(name: &str) -> Result<PathBuf> {
+    let name = name.trim();
+    if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+        anyhow::bail!("'{}' isn't a deck name - use a plain name like \"mine\" or \"objects\"", name);
+    }
+
+    let dir = decks_dir()?;
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join(format!("{}.toml", name)))
+}
+
+fn parse_deck(path: &Path) -> Result<DeckFile> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read prompt deck at {}", path.display()))?;
+
+    let deck = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {} - expected [[prompts]] entries", path.display()))?,
+        Some("json") => serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {} - expected a \"prompts\" array", path.display()))?,
+        _ => DeckFile {
+            name: None,
+            prompts: content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| Card { text: line.to_string(), tags: Vec::new(), deck: String::new() })
+                .collect(),
+        },
+    };
+    Ok(deck)
+}
+
+fn loaded(file: DeckFile, default_name: &str, path: Option<PathBuf>) -> LoadedDeck {
+    let name = file.name.unwrap_or_else(|| default_name.to_string());
+    let cards = file
+        .prompts
+        .into_iter()
+        .map(|card| Card { deck: name.clone(), ..card })
+        .collect();
+
+    LoadedDeck { name, path, cards }
+}
+
+fn load_file(path: &Path) -> Result<LoadedDeck> {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("deck");
+    Ok(loaded(parse_deck(path)?, stem, Some(path.to_path_buf())))
+}
+
+// The bundled deck followed by every deck in the data directory.
+pub fn load_all() -> Result<Vec<LoadedDeck>> {
+    let bundled: DeckFile = toml::from_str(BUNDLED).context("Bundled prompt deck is invalid")?;
+    let mut decks = vec![loaded(bundled, "default", None)];
+
+    let dir = decks_dir()?;
+    if dir.exists() {
+        let mut paths: Vec<PathBuf> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match load_file(&path) {
+                Ok(deck) => decks.push(deck),
+                Err(e) => eprintln!("Warning: Skipping deck {}: {:#}", path.display(), e),
+            }
+        }
+    }
+
+    Ok(decks)
+}
+
+// The day's card: among the cards this journal has used least (so none
+// repeats until every card has had its turn), one chosen by a hash of the
+// journal's seed and the date. The same day always gives the same card.
+pub fn pick<'a>(cards: &'a [Card], usage: &HashMap<String, usize>, seed: &str, day: NaiveDate) -> Option<&'a Card> {
+    let uses = |card: &Card| usage.get(&card.text).copied().unwrap_or(0);
+    let fewest = cards.iter().map(uses).min()?;
+
+    let mut candidates: Vec<&Card> = cards.iter().filter(|card| uses(card) == fewest).collect();
+    // Order by content rather than file position, so editing a deck only
+    // changes the picks that involve the edited cards.
+    candidates.sort_by_key(|card| (stable_hash(&card.text), &card.text));
+
+    let roll = stable_hash(&format!("{}:{}", seed, day.num_days_from_ce()));
+    candidates.get((roll % candidates.len() as u64) as usize).copied()
+}
+
+impl Database {
+    pub fn journal_seed(&self) -> Result<String> {
+        Ok(self
+            .conn
+            .query_row("SELECT value FROM journal_meta WHERE key = 'seed'", [], |row| row.get(0))?)
+    }
+
+    // How many entries have used each prompt text, forgotten ones included.
+    pub fn prompt_usage(&self) -> Result<HashMap<String, usize>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT prompt, COUNT(*) FROM creative_prompts WHERE prompt != '' GROUP BY prompt")?;
+        let usage = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(usage)
+    }
+}
+
+// Draws from one deck file, or from every deck when `path` is None.
+pub struct Deck {
+    path: Option<PathBuf>,
+    seed: String,
+    usage: HashMap<String, usize>,
+}
+
+impl Deck {
+    pub fn new(path: Option<PathBuf>, db: &Database) -> Result<Self> {
+        Ok(Deck {
+            path,
+            seed: db.journal_seed()?,
+            usage: db.prompt_usage()?,
+        })
+    }
+
+    // Every distinct card, first deck wins
+    fn cards(&self) -> Result<Vec<Card>> {
+        let decks = match &self.path {
+            Some(path) => vec![load_file(path)?],
+            None => load_all()?,
+        };
+
+        let mut seen = HashSet::new();
+        Ok(decks
+            .into_iter()
+            .flat_map(|deck| deck.cards)
+            .filter(|card| seen.insert(card.text.clone()))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl PromptProvider for Deck {
+    fn name(&self) -> String {
+        match &self.path {
+            Some(path) => format!("deck {}", path.display()),
+            None => "prompt deck".to_string(),
+        }
+    }
+
+    async fn generate(&self, request: &PromptRequest) -> Result<Prompt> {
+        let cards = self.cards()?;
+        let card = pick(&cards, &self.usage, &self.seed, request.day).context("The prompt deck is empty")?;
+
+        Ok(Prompt::new(card.text.clone(), "deck")
+            .with("deck", card.deck.clone())
+            .with("prompt_tags", card.tags.clone()))
+    }
+
+    async fn check(&self) -> Result<String> {
+        match self.cards()?.len() {
+            0 => anyhow::bail!("The prompt deck is empty"),
+            count => Ok(format!("{} prompts", count)),
+        }
+    }
+}
+
+pub fn add_card(text: &str, card_tags: &[String], deck: Option<&str>) -> Result<PathBuf> {
+    let text = text.trim();
+    if text.is_empty() {
+        anyhow::bail!("A prompt needs some text");
+    }
+
+    let path = deck_path(deck.unwrap_or(DEFAULT_USER_DECK))?;
+
+    let mut file = if path.exists() { parse_deck(&path)? } else { DeckFile::default() };
+    if file.prompts.iter().any(|card| card.text == text) {
+        anyhow::bail!("That prompt is already in {}", path.display());
+    }
+    file.prompts.push(Card { text: text.to_string(), tags: tags::normalize_tags(card_tags), deck: String::new() });
+
+    fs::write(&path, toml::to_string_pretty(&file)?)?;
+    Ok(path)
+}
+
+// Copy a deck into the data directory as TOML, merging with an existing
+// deck of the same name. Returns the destination and how many prompts were new.
+pub fn import_deck(source: &Path, name: Option<&str>) -> Result<(PathBuf, usize)> {
+    let incoming = parse_deck(source)?;
+    if incoming.prompts.is_empty() {
+        anyhow::bail!("No prompts found in {}", source.display());
+    }
+
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => source.file_stem().and_then(|s| s.to_str()).context("Deck needs a name - pass --name")?.to_string(),
+    };
+
+    let path = deck_path(&name)?;
+
+    let mut file = if path.exists() { parse_deck(&path)? } else { DeckFile { name: incoming.name, prompts: Vec::new() } };
+    let mut known: HashSet<String> = file.prompts.iter().map(|card| card.text.clone()).collect();
+
+    let mut added = 0;
+    for card in incoming.prompts {
+        let text = card.text.trim().to_string();
+        if !text.is_empty() && known.insert(text.clone()) {
+            file.prompts.push(Card { text, tags: tags::normalize_tags(&card.tags), deck: String::new() });
+            added += 1;
+        }
+    }
+
+    fs::write(&path, toml::to_string_pretty(&file)?)?;
+    Ok((path, added))
+}
+
+pub fn print_decks(db: &Database, show_prompts: bool) -> Result<()> {
+    let usage = db.prompt_usage()?;
+
+    for deck in load_all()? {
+        let used = deck.cards.iter().filter(|card| usage.contains_key(&card.text)).count();
+        let location = match &deck.path {
+            Some(path) => path.display().to_string(),
+            None => "bundled".to_string(),
+        };
+        println!("📚 {} - {} prompts, {} drawn ({})", deck.name, deck.cards.len(), used, location);
+
+        if show_prompts {
+            for card in &deck.cards {
+                let mark = if usage.contains_key(&card.text) { "✓" } else { " " };
+                if card.tags.is_empty() {
+                    println!("   {} {}", mark, card.text);
+                } else {
+                    println!("   {} {} {}", mark, card.text, tags::format_tags(&card.tags));
+                }
+            }
+            println!();
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cards(count: usize) -> Vec<Card> {
+        (0..count)
+            .map(|n| Card { text: format!("Prompt number {}", n), tags: Vec::new(), deck: "test".to_string() })
+            .collect()
+    }
+
+    fn day(offset: i64) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 1).unwrap() + chrono::Duration::days(offset)
+    }
+
+    #[test]
+    fn the_same_day_gives_the_same_card() {
+        let cards = cards(12);
+        let usage = HashMap::new();
+
+        let first = pick(&cards, &usage, "seed", day(0)).unwrap();
+        for _ in 0..5 {
+            assert_eq!(pick(&cards, &usage, "seed", day(0)).unwrap().text, first.text);
+        }
+    }
+
+    #[test]
+    fn no_card_repeats_until_the_deck_is_used_up() {
+        let cards = cards(12);
+        let mut usage: HashMap<String, usize> = HashMap::new();
+        let mut drawn = HashSet::new();
+
+        for offset in 0..cards.len() as i64 {
+            let card = pick(&cards, &usage, "seed", day(offset)).unwrap();
+            assert!(drawn.insert(card.text.clone()), "{} came up twice", card.text);
+            *usage.entry(card.text.clone()).or_default() += 1;
+        }
+        assert_eq!(drawn.len(), cards.len());
+
+        // Then a fresh round begins, still from the least used cards
+        let card = pick(&cards, &usage, "seed", day(cards.len() as i64)).unwrap();
+        assert_eq!(usage[&card.text], 1);
+    }
+
+    #[test]
+    fn an_empty_deck_has_no_card() {
+        assert!(pick(&[], &HashMap::new(), "seed", day(0)).is_none());
+    }
+
+    #[test]
+    fn deck_names_stay_inside_the_decks_directory() {
+        for name in ["", "  ", "../../x", "..", "a/b", "a\\b", "x..y"] {
+            assert!(deck_path(name).is_err(), "{:?} should be refused", name);
+        }
+    }
+}
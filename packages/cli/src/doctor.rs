@@ -0,0 +1,239 @@
+use anyhow::Result;
+use rusqlite::{Connection, OpenFlags};
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::calendar::Calendar;
+use crate::providers::ProviderChain;
+use crate::editor::Editor;
+use crate::{config, get_db_path, metrics, migrations, Config, Database};
+
+const PROVIDERS: &str = "🌀 Prompt providers (in the order they're tried)";
+
+// Tallies the checks as they're printed
+#[derive(Default)]
+struct Report {
+    failures: usize,
+}
+
+impl Report {
+    fn section(&self, title: &str) {
+        println!("\n{}", title);
+    }
+
+    fn pass(&self, detail: impl AsRef<str>) {
+        println!("  ✓ {}", detail.as_ref());
+    }
+
+    fn skip(&self, reason: &str) {
+        println!("  - {}", reason);
+    }
+
+    // Something wrong, and what to do about it
+    fn fail(&mut self, problem: impl AsRef<str>, fix: impl AsRef<str>) {
+        self.failures += 1;
+        println!("  ✗ {}", problem.as_ref());
+        println!("    → {}", fix.as_ref());
+    }
+}
+
+// Check everything estrange depends on and say how to fix what's broken.
+// Fails (so the exit code is non-zero) if any check does.
+pub async fn run(explicit: Option<&Path>) -> Result<()> {
+    println!("🩺 Checking your estrange setup...");
+    let mut report = Report::default();
+
+    let config = check_config(&mut report, explicit);
+    let db = check_database(&mut report, &config);
+    check_editor(&mut report, &config);
+    match &db {
+        Some(db) => check_providers(&mut report, &config, db).await,
+        None => {
+            report.section(PROVIDERS);
+            report.skip("Needs the journal, which couldn't be opened");
+        }
+    }
+    check_metrics(&mut report, &config).await;
+
+    match report.failures {
+        0 => {
+            println!("\n✨ Everything looks ready.");
+            Ok(())
+        }
+        1 => anyhow::bail!("1 check failed"),
+        n => anyhow::bail!("{} checks failed", n),
+    }
+}
+
+// The loaded config, or the defaults when it can't be loaded, so the other
+// checks still run.
+fn check_config(report: &mut Report, explicit: Option<&Path>) -> Config {
+    report.section("⚙️  Config");
+
+    match config::layers(explicit) {
+        Ok(layers) => {
+            let found: Vec<_> = layers.iter().filter(|layer| layer.path.exists()).collect();
+            if found.is_empty() {
+                report.pass("No config file - using defaults (create one with: estrange config init)");
+            }
+            for layer in found {
+                report.pass(format!("Reading {} ({})", layer.path.display(), layer.source));
+            }
+        }
+        Err(e) => report.fail(format!("{:#}", e), "Set ESTRANGE_CONFIG or pass --config to name a config file"),
+    }
+
+    let config = match Config::load(explicit) {
+        Ok(config) => config,
+        Err(e) => {
+            report.fail(
+                format!("{:#}", e),
+                "Fix the file (see config.example.toml), or list the files read with: estrange config path",
+            );
+            return Config::default();
+        }
+    };
+
+    match config.validate() {
+        Ok(()) => report.pass("Settings are valid"),
+        Err(e) => report.fail(format!("{:#}", e), "Change the setting with: estrange config set <key> <value>"),
+    }
+    config
+}
+
+// The journal, opened the usual way when it's healthy, for the checks that
+// need it. A missing journal is created and an old one upgraded, as on any run.
+fn check_database(report: &mut Report, config: &Config) -> Option<Database> {
+    report.section("🗄️  Journal");
+
+    let path = match get_db_path() {
+        Ok(path) => path,
+        Err(e) => {
+            report.fail(format!("{:#}", e), "Set HOME (or XDG_DATA_HOME) so estrange has somewhere to keep your journal");
+            return None;
+        }
+    };
+
+    if path.exists() {
+        match inspect_database(&path) {
+            Ok(status) => report.pass(format!("{} ({})", path.display(), status)),
+            Err(e) => {
+                report.fail(
+                    format!("{}: {:#}", path.display(), e),
+                    "Restore a backup (estrange.db.v*.bak beside it) or import your last archive with: estrange import",
+                );
+                return None;
+            }
+        }
+    } else {
+        report.pass(format!("{} (new journal, created now)", path.display()));
+    }
+
+    // An unusable timezone was already reported with the config
+    let calendar = Calendar::from_config(config).or_else(|_| Calendar::from_config(&Config::default()));
+    match calendar.and_then(Database::new) {
+        Ok(db) => Some(db),
+        Err(e) => {
+            report.fail(format!("{:#}", e), format!("Check that {} is writable", path.display()));
+            None
+        }
+    }
+}
+
+// Read-only, so a damaged journal isn't made worse
+fn inspect_database(path: &Path) -> Result<String> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let problems: Vec<String> = conn
+        .prepare("PRAGMA integrity_check")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    if problems.first().map(String::as_str) != Some("ok") {
+        anyhow::bail!("integrity check failed: {}", problems.join("; "));
+    }
+
+    let version = migrations::schema_version(&conn)?;
+    let latest = migrations::latest_version();
+    if version > latest {
+        anyhow::bail!("schema version {} is newer than this estrange understands ({})", version, latest);
+    }
+    if version < latest {
+        // Opening it below for the provider checks runs the migrations
+        return Ok(format!("intact, schema v{} - upgrading to v{} now, after a backup", version, latest));
+    }
+    Ok(format!("intact, schema v{}", version))
+}
+
+fn check_editor(report: &mut Report, config: &Config) {
+    report.section("✏️  Editor");
+
+    let editor = match Editor::from_config(config) {
+        Ok(editor) => editor,
+        Err(e) => {
+            report.fail(format!("{:#}", e), "Set VISUAL or EDITOR to a command line, e.g. export EDITOR=\"code --wait\"");
+            return;
+        }
+    };
+    match find_executable(&editor.program) {
+        Some(path) => report.pass(format!(
+            "{} (from {}, {}), writing .{} files",
+            editor.command_line(),
+            editor.source,
+            path.display(),
+            editor.extension
+        )),
+        None => report.fail(
+            format!("{} (from {}) isn't an executable on your PATH", editor.program, editor.source),
+            "Set VISUAL or EDITOR to an installed editor, e.g. export EDITOR=nano",
+        ),
+    }
+}
+
+// Where `command` would be run from, as a shell would find it
+fn find_executable(command: &str) -> Option<PathBuf> {
+    let command = Path::new(command);
+    if command.components().count() > 1 {
+        return command.is_file().then(|| command.to_path_buf());
+    }
+
+    env::split_paths(&env::var_os("PATH")?).find_map(|dir| {
+        [dir.join(command), dir.join(command).with_extension("exe")]
+            .into_iter()
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+async fn check_providers(report: &mut Report, config: &Config, db: &Database) {
+    report.section(PROVIDERS);
+
+    let chain = match ProviderChain::from_config(config, db) {
+        Ok(chain) => chain,
+        Err(e) => {
+            report.fail(format!("{:#}", e), "Fix the [[providers]] entries in your config (see config.example.toml)");
+            return;
+        }
+    };
+
+    for (name, result) in chain.check().await {
+        match result {
+            Ok(detail) => report.pass(format!("{}: {}", name, detail)),
+            Err(e) => report.fail(
+                format!("{}: {:#}", name, e),
+                "Check its key, URL and model - until then estrange falls through to the next provider",
+            ),
+        }
+    }
+}
+
+async fn check_metrics(report: &mut Report, config: &Config) {
+    report.section("📊 Metrics");
+
+    match metrics::check_grafana(config).await {
+        Ok(None) => report.pass("Not sending metrics (set metrics.grafana_push_url to send them)"),
+        Ok(Some(url)) => report.pass(format!("Grafana accepted the credentials for {}", url)),
+        Err(e) => report.fail(
+            format!("{:#}", e),
+            "Check grafana_user and grafana_token under [metrics], or turn sending off with: estrange config set metrics.metrics_enabled false",
+        ),
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::template;
+use crate::{get_db_path, Database};
+
+// A response in progress, saved next to the journal as `drafts/<sync_id>.md`
+// (or whichever editor.extension is set) so it outlives editor crashes and
+// failed saves.
+pub struct Draft {
+    pub path: PathBuf,
+    pub sync_id: String,
+    pub saved_at: DateTime<Local>,
+    pub words: usize,
+}
+
+// Where a draft stands relative to the entry it was written for.
+pub enum DraftStatus {
+    // Today's prompt, still unanswered - `estrange` offers to resume it
+    Resumable { id: i64, prompt: String },
+    Abandoned { id: i64, prompt: String, reason: &'static str },
+    Orphaned,
+}
+
+pub fn drafts_dir() -> Result<PathBuf> {
+    let mut path = get_db_path()?;
+    path.set_file_name("drafts");
+    Ok(path)
+}
+
+// The entry's draft if it has one, whatever its extension, else where a new
+// one with `extension` would go
+pub fn draft_path(sync_id: &str, extension: &str) -> Result<PathBuf> {
+    let dir = drafts_dir()?;
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create drafts directory {}", dir.display()))?;
+    match find_draft(sync_id)? {
+        Some(existing) => Ok(existing),
+        None => Ok(dir.join(format!("{}.{}", sync_id, extension))),
+    }
+}
+
+fn find_draft(sync_id: &str) -> Result<Option<PathBuf>> {
+    Ok(list_drafts()?.into_iter().find(|draft| draft.sync_id == sync_id).map(|draft| draft.path))
+}
+
+fn read_draft(path: &Path) -> Option<Draft> {
+    let sync_id = path.file_stem()?.to_str()?.to_string();
+    let content = fs::read_to_string(path).ok()?;
+    let saved_at = fs::metadata(path).and_then(|m| m.modified()).ok()?.into();
+
+    Some(Draft {
+        path: path.to_path_buf(),
+        sync_id,
+        saved_at,
+        words: template::word_count(&content),
+    })
+}
+
+pub fn list_drafts() -> Result<Vec<Draft>> {
+    let dir = drafts_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut drafts: Vec<Draft> = fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some())
+        .filter_map(|path| read_draft(&path))
+        .collect();
+
+    drafts.sort_by_key(|draft| std::cmp::Reverse(draft.saved_at));
+    Ok(drafts)
+}
+
+impl Database {
+    pub fn entry_sync_id(&self, id: i64) -> Result<String> {
+        Ok(self.conn.query_row("SELECT sync_id FROM creative_prompts WHERE id = ?1", [id], |row| row.get(0))?)
+    }
+
+    pub fn draft_status(&self, draft: &Draft) -> Result<DraftStatus> {
+        let today = self.get_today_entry()?;
+        if let Some(entry) = today.filter(|e| e.pending) {
+            if self.entry_sync_id(entry.id)? == draft.sync_id {
+                return Ok(DraftStatus::Resumable { id: entry.id, prompt: entry.prompt });
+            }
+        }
+
+        let row = self.conn.query_row(
+            "SELECT id, prompt, deleted_at IS NOT NULL, response IS NOT NULL OR drawing_vector IS NOT NULL
+             FROM creative_prompts WHERE sync_id = ?1",
+            [&draft.sync_id],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, bool>(2)?, row.get::<_, bool>(3)?)),
+        );
+
+        Ok(match row {
+            Ok((id, prompt, true, _)) => DraftStatus::Abandoned { id, prompt, reason: "entry forgotten" },
+            Ok((id, prompt, _, true)) => DraftStatus::Abandoned { id, prompt, reason: "already answered" },
+            Ok((id, prompt, _, false)) => DraftStatus::Abandoned { id, prompt, reason: "prompt from an earlier day" },
+            Err(rusqlite::Error::QueryReturnedNoRows) => DraftStatus::Orphaned,
+            Err(e) => return Err(e.into()),
+        })
+    }
+}
+
+// Ask whether to pick up an unfinished draft. Returns false when there is
+// nothing worth resuming or the user wants a fresh page.
+pub fn offer_resume(path: &Path) -> Result<bool> {
+    let Some(draft) = read_draft(path) else {
+        return Ok(false);
+    };
+    if draft.words == 0 {
+        return Ok(false);
+    }
+
+    println!(
+        "📝 Found an unfinished draft ({} words, saved {})",
+        draft.words,
+        draft.saved_at.format("%Y-%m-%d %H:%M")
+    );
+    print!("Resume it? [Y/n] ");
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+
+    let resume = matches!(answer.trim().to_lowercase().as_str(), "" | "y" | "yes");
+    if !resume {
+        println!("🗑️  Starting from a blank page");
+    }
+    Ok(resume)
+}
+
+pub fn print_drafts(db: &Database) -> Result<()> {
+    let drafts = list_drafts()?;
+    if drafts.is_empty() {
+        println!("📝 No drafts in progress");
+        return Ok(());
+    }
+
+    println!("📝 {} draft(s):", drafts.len());
+    println!();
+    for draft in &drafts {
+        let saved = draft.saved_at.format("%Y-%m-%d %H:%M");
+        match db.draft_status(draft)? {
+            DraftStatus::Resumable { id, prompt } => {
+                println!("ID: {} | Saved: {} | {} words | resumes with 'estrange'", id, saved, draft.words);
+                println!("Prompt: {}", prompt);
+            }
+            DraftStatus::Abandoned { id, prompt, reason } => {
+                println!("ID: {} | Saved: {} | {} words | abandoned ({})", id, saved, draft.words, reason);
+                println!("Prompt: {}", prompt);
+            }
+            DraftStatus::Orphaned => {
+                println!("Saved: {} | {} words | abandoned (entry no longer exists)", saved, draft.words);
+            }
+        }
+        println!("File: {}", draft.path.display());
+        println!();
+    }
+    Ok(())
+}
+
+// Discard one entry's draft, or every abandoned draft when no ID is given.
+pub fn discard_drafts(db: &Database, id: Option<i64>) -> Result<()> {
+    if let Some(id) = id {
+        let sync_id = db.entry_sync_id(id).with_context(|| format!("No entry with ID {}", id))?;
+        let path = find_draft(&sync_id)?.with_context(|| format!("Entry {} has no draft", id))?;
+        fs::remove_file(&path)?;
+        println!("🗑️  Discarded the draft for entry {}", id);
+        return Ok(());
+    }
+
+    let mut discarded = 0;
+    for draft in list_drafts()? {
+        if !matches!(db.draft_status(&draft)?, DraftStatus::Resumable { .. }) {
+            fs::remove_file(&draft.path)?;
+            discarded += 1;
+        }
+    }
+    println!("🗑️  Discarded {} abandoned draft(s)", discarded);
+    Ok(())
+}
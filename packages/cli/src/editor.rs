@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::template::{self, FrontMatterFormat};
+use crate::Config;
+
+const DEFAULT_EDITOR: &str = "vi";
+const DEFAULT_EXTENSION: &str = "md";
+
+// The `[editor]` section of config.toml
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct EditorConfig {
+    // Used when neither $VISUAL nor $EDITOR is set, e.g. "code --wait"
+    pub command: Option<String>,
+    // Extension of the files responses are written in, so the editor picks
+    // a syntax mode. Defaults to "md".
+    pub extension: Option<String>,
+    // "yaml" or "toml" to open new responses with a front-matter block
+    // whose title, mood and tags are saved with the entry
+    pub front_matter: Option<FrontMatterFormat>,
+}
+
+impl EditorConfig {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(command) = &self.command {
+            split(command, "editor.command")?;
+        }
+        if let Some(extension) = &self.extension {
+            parse_extension(extension)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_extension(extension: &str) -> Result<String> {
+    let extension = extension.trim_start_matches('.');
+    if extension.is_empty() || extension.contains(['/', '\\']) {
+        anyhow::bail!("editor.extension should look like \"md\" or \"txt\", got '{}'", extension);
+    }
+    Ok(extension.to_string())
+}
+
+// Split a command line the way a shell would, so quoted paths and
+// arguments (`code --wait`) survive.
+fn split(command: &str, source: &str) -> Result<Vec<String>> {
+    let words = shell_words::split(command).with_context(|| format!("Couldn't parse {} '{}'", source, command))?;
+    if words.is_empty() {
+        anyhow::bail!("{} is empty", source);
+    }
+    Ok(words)
+}
+
+// The editor responses are written in, and the files they're written to
+pub struct Editor {
+    pub program: String,
+    args: Vec<String>,
+    // Where the command came from, for messages
+    pub source: &'static str,
+    pub extension: String,
+    pub front_matter: Option<FrontMatterFormat>,
+}
+
+impl Editor {
+    // $VISUAL, then $EDITOR, then `editor.command` in the config, then vi
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let settings = config.editor.clone().unwrap_or_default();
+        let from_env = |var: &str| env::var(var).ok().filter(|value| !value.trim().is_empty());
+
+        let (command, source) = if let Some(command) = from_env("VISUAL") {
+            (command, "$VISUAL")
+        } else if let Some(command) = from_env("EDITOR") {
+            (command, "$EDITOR")
+        } else if let Some(command) = settings.command {
+            (command, "editor.command")
+        } else {
+            (DEFAULT_EDITOR.to_string(), "the default")
+        };
+
+        let mut words = split(&command, source)?;
+        let program = words.remove(0);
+        let mut args = words;
+
+        // GUI editors return at once unless told to wait for the file to close,
+        // which would leave an empty response
+        if let Some(flag) = wait_flag(&program) {
+            if !args.iter().any(|arg| arg == flag || arg == "-w") {
+                args.push(flag.to_string());
+            }
+        }
+
+        Ok(Editor {
+            program,
+            args,
+            source,
+            extension: parse_extension(settings.extension.as_deref().unwrap_or(DEFAULT_EXTENSION))?,
+            front_matter: settings.front_matter,
+        })
+    }
+
+    // The program's name without directory or .exe, e.g. "nvim"
+    fn name(&self) -> String {
+        Path::new(&self.program)
+            .file_stem()
+            .map_or_else(|| self.program.clone(), |stem| stem.to_string_lossy().to_string())
+    }
+
+    // The whole command line, as it's run
+    pub fn command_line(&self) -> String {
+        shell_words::join(std::iter::once(&self.program).chain(&self.args))
+    }
+
+    // Open `path` and return what was written above the scissors line.
+    // Editors that can start on a given line open where the writing goes.
+    pub fn edit(&self, path: &Path) -> Result<String> {
+        let line = template::first_writing_line(&fs::read_to_string(path)?);
+
+        let mut command = Command::new(&self.program);
+        command.args(&self.args);
+        match self.name().as_str() {
+            "vi" | "vim" | "nvim" | "nano" => {
+                command.arg(format!("+{}", line)).arg(path);
+            }
+            "hx" | "helix" => {
+                command.arg(format!("{}:{}", path.display(), line));
+            }
+            _ => {
+                command.arg(path);
+            }
+        }
+
+        let status = command
+            .status()
+            .with_context(|| format!("Failed to open editor: {} (from {})", self.command_line(), self.source))?;
+
+        if !status.success() {
+            anyhow::bail!("Editor exited with non-zero status");
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(template::response_text(&content))
+    }
+}
+
+fn wait_flag(program: &str) -> Option<&'static str> {
+    let name = Path::new(program).file_stem()?.to_str()?;
+    match name {
+        "code" | "code-insiders" | "codium" | "cursor" | "subl" | "zed" => Some("--wait"),
+        "mate" => Some("-w"),
+        _ => None,
+    }
+}
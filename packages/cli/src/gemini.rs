@@ -0,0 +1,237 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::env;
+
+use crate::generation::GenerationConfig;
+use crate::http::HttpClient;
+use crate::providers::{Prompt, PromptProvider, PromptRequest};
+use crate::{secrets, tags};
+
+const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com";
+const DEFAULT_MODEL: &str = "gemini-2.5-flash";
+// Checked against every answer, since models don't always keep to the
+// response schema they were given.
+const SCHEMA: &str = include_str!("../schema/generated-prompt.schema.json");
+
+// API structures for Gemini
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<GeminiContent>,
+    contents: Vec<GeminiContent>,
+    generation_config: Value,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GeminiContent {
+    parts: Vec<GeminiPart>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GeminiPart {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct GeminiResponse {
+    candidates: Vec<GeminiCandidate>,
+}
+
+#[derive(Deserialize)]
+struct GeminiCandidate {
+    content: GeminiContent,
+}
+
+// The structured answer, as described by schema/generated-prompt.schema.json
+#[derive(Deserialize)]
+struct GeneratedPrompt {
+    prompt: String,
+    tags: Vec<String>,
+    follow_ups: Vec<String>,
+}
+
+// Ask for JSON in the same shape as the worker's daily prompt, plus a few
+// questions to carry into the response.
+fn generation_config(settings: &GenerationConfig) -> Value {
+    let mut config = json!({
+        "responseMimeType": "application/json",
+        "responseSchema": {
+            "type": "OBJECT",
+            "properties": {
+                "prompt": {
+                    "type": "STRING",
+                    "description": "The creative stimulus itself, with no preamble, quotes or explanation"
+                },
+                "tags": {
+                    "type": "ARRAY",
+                    "items": { "type": "STRING" },
+                    "description": "A few lowercase themes the prompt touches"
+                },
+                "follow_ups": {
+                    "type": "ARRAY",
+                    "items": { "type": "STRING" },
+                    "minItems": 2,
+                    "maxItems": 3,
+                    "description": "Two or three short questions that open the prompt up, like: How would cities adapt? What would Tuesday fashion look like?"
+                }
+            },
+            "required": ["prompt", "tags", "follow_ups"],
+            "propertyOrdering": ["prompt", "tags", "follow_ups"]
+        }
+    });
+    if let Some(temperature) = settings.temperature {
+        config["temperature"] = json!(temperature);
+    }
+    if let Some(top_p) = settings.top_p {
+        config["topP"] = json!(top_p);
+    }
+    config
+}
+
+fn parse_generated(text: &str) -> Result<GeneratedPrompt> {
+    let value: Value =
+        serde_json::from_str(text).with_context(|| format!("Gemini didn't answer with JSON: {}", text.trim()))?;
+
+    let schema: Value = serde_json::from_str(SCHEMA).context("Bundled prompt schema is not valid JSON")?;
+    let validator = jsonschema::validator_for(&schema)
+        .map_err(|e| anyhow::anyhow!("Bundled prompt schema is invalid: {}", e))?;
+
+    let errors: Vec<String> = validator
+        .iter_errors(&value)
+        .map(|e| format!("{} at {}", e, e.instance_path))
+        .collect();
+
+    if !errors.is_empty() {
+        anyhow::bail!("Gemini's answer failed schema validation:\n  {}", errors.join("\n  "));
+    }
+
+    Ok(serde_json::from_value(value)?)
+}
+
+// API client
+pub struct GeminiClient {
+    http: HttpClient,
+    api_key: Option<String>,
+    model: String,
+    base_url: String,
+    generation: GenerationConfig,
+}
+
+impl GeminiClient {
+    // The key can come from the provider entry, the top-level
+    // `gemini_api_key`, or GEMINI_API_KEY. A missing key only fails when the
+    // provider is actually asked for a prompt, so later providers in the
+    // chain still get their turn.
+    pub fn new(
+        http: HttpClient,
+        api_key: Option<String>,
+        model: Option<String>,
+        base_url: Option<String>,
+        generation: GenerationConfig,
+    ) -> Self {
+        GeminiClient {
+            http,
+            api_key: api_key.or_else(|| env::var("GEMINI_API_KEY").ok()).filter(|key| !key.is_empty()),
+            model: model.unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            base_url: base_url.unwrap_or_else(|| DEFAULT_BASE_URL.to_string()),
+            generation,
+        }
+    }
+
+    // Resolved on each use, so a key that can't be resolved fails this
+    // provider like a missing one does
+    fn api_key(&self) -> Result<String> {
+        let reference = self
+            .api_key
+            .as_deref()
+            .context("No Gemini API key found. Set one with: estrange config set gemini_api_key YOUR_KEY (or export GEMINI_API_KEY)")?;
+        secrets::resolve_setting("the Gemini API key", reference)
+    }
+}
+
+#[async_trait]
+impl PromptProvider for GeminiClient {
+    fn name(&self) -> String {
+        format!("gemini ({})", self.model)
+    }
+
+    async fn generate(&self, request: &PromptRequest) -> Result<Prompt> {
+        let api_key = self.api_key()?;
+
+        let body = GeminiRequest {
+            system_instruction: self.generation.system_instruction.as_ref().map(|text| GeminiContent {
+                parts: vec![GeminiPart { text: text.clone() }],
+            }),
+            contents: vec![GeminiContent {
+                parts: vec![GeminiPart {
+                    text: request.instruction.clone(),
+                }],
+            }],
+            generation_config: generation_config(&self.generation),
+        };
+
+        // The key goes in a header, never the URL, so it can't end up in
+        // proxy logs or error messages
+        let url = format!(
+            "{}/v1beta/models/{}:generateContent",
+            self.base_url.trim_end_matches('/'),
+            self.model
+        );
+        let http_request = self.http.post(&url).header("x-goog-api-key", api_key).json(&body);
+
+        let response = self
+            .http
+            .send(http_request)
+            .await
+            .context("Failed to send request to Gemini API")?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            anyhow::bail!("Gemini API error: {}", error_text);
+        }
+
+        let gemini_response: GeminiResponse = response
+            .json()
+            .await
+            .context("Failed to parse Gemini API response")?;
+
+        let generated_text = gemini_response
+            .candidates
+            .first()
+            .and_then(|c| c.content.parts.first())
+            .map(|p| p.text.as_str())
+            .context("No content in Gemini API response")?;
+        let generated = parse_generated(generated_text)?;
+
+        let follow_ups: Vec<String> = generated.follow_ups.iter().map(|q| q.trim().to_string()).collect();
+        let prompt = Prompt::new(generated.prompt.trim().to_string(), "gemini")
+            .with("model", self.model.clone())
+            .with("generator", request.generator.clone())
+            // Become the entry's tags when it's saved
+            .with("prompt_tags", tags::normalize_tags(&generated.tags))
+            .with("follow_ups", follow_ups);
+        Ok(self.generation.record(prompt))
+    }
+
+    // Looking the model up checks the key and the model name for free
+    async fn check(&self) -> Result<String> {
+        let api_key = self.api_key()?;
+        let url = format!("{}/v1beta/models/{}", self.base_url.trim_end_matches('/'), self.model);
+
+        let response = self
+            .http
+            .send(self.http.get(&url).header("x-goog-api-key", api_key))
+            .await
+            .context("Failed to reach the Gemini API")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            anyhow::bail!("Gemini API error {}: {}", status, error_text.trim());
+        }
+        Ok(format!("API key accepted, model {} available", self.model))
+    }
+}
@@ -0,0 +1,104 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::providers::Prompt;
+use crate::Database;
+
+// Sampling settings for model-backed providers. The top-level
+// `[generation]` table applies to all of them; the same keys on a
+// `[[providers]]` entry override it for that provider.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct GenerationConfig {
+    pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+    // Sent as the system prompt, ahead of the generator's instruction
+    pub system_instruction: Option<String>,
+}
+
+impl GenerationConfig {
+    // These settings, with any unset ones taken from `defaults`
+    pub fn or(&self, defaults: Option<&GenerationConfig>) -> GenerationConfig {
+        let defaults = defaults.cloned().unwrap_or_default();
+        GenerationConfig {
+            temperature: self.temperature.or(defaults.temperature),
+            top_p: self.top_p.or(defaults.top_p),
+            system_instruction: self.system_instruction.clone().or(defaults.system_instruction),
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(temperature) = self.temperature {
+            if !(0.0..=2.0).contains(&temperature) {
+                anyhow::bail!("temperature must be between 0 and 2, got {}", temperature);
+            }
+        }
+        if let Some(top_p) = self.top_p {
+            if !(0.0..=1.0).contains(&top_p) {
+                anyhow::bail!("top_p must be between 0 and 1, got {}", top_p);
+            }
+        }
+        Ok(())
+    }
+
+    // Record the settings that produced `prompt`. Unset ones are left out,
+    // meaning the model's own defaults were used.
+    pub fn record(&self, mut prompt: Prompt) -> Prompt {
+        if let Some(temperature) = self.temperature {
+            prompt = prompt.with("temperature", temperature);
+        }
+        if let Some(top_p) = self.top_p {
+            prompt = prompt.with("top_p", top_p);
+        }
+        if let Some(system_instruction) = &self.system_instruction {
+            prompt = prompt.with("system_instruction", system_instruction.clone());
+        }
+        prompt
+    }
+}
+
+pub struct TemperatureStats {
+    // None for prompts made at the model's default temperature
+    pub temperature: Option<f64>,
+    pub entries: u32,
+    pub average_words: f64,
+    // Distinct words per response - how widely it ranges
+    pub average_vocabulary: f64,
+}
+
+impl Database {
+    // Responses grouped by the temperature of the prompt that led to them.
+    // Only prompts a model generated are counted.
+    pub fn temperature_stats(&self) -> Result<Vec<TemperatureStats>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT json_extract(metadata, '$.temperature'), response
+             FROM creative_prompts
+             WHERE deleted_at IS NULL AND response IS NOT NULL
+               AND json_extract(metadata, '$.provider') IN ('gemini', 'openai-compatible')",
+        )?;
+
+        // Keyed by temperature in hundredths, so the map sorts numerically
+        let mut groups: BTreeMap<Option<i64>, (u32, usize, usize)> = BTreeMap::new();
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, Option<f64>>(0)?, row.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (temperature, response) = row?;
+            let words: Vec<String> = response.split_whitespace().map(str::to_lowercase).collect();
+            let vocabulary = words.iter().collect::<HashSet<_>>().len();
+
+            let group = groups.entry(temperature.map(|t| (t * 100.0).round() as i64)).or_default();
+            group.0 += 1;
+            group.1 += words.len();
+            group.2 += vocabulary;
+        }
+
+        Ok(groups
+            .into_iter()
+            .map(|(temperature, (entries, words, vocabulary))| TemperatureStats {
+                temperature: temperature.map(|t| t as f64 / 100.0),
+                entries,
+                average_words: words as f64 / entries as f64,
+                average_vocabulary: vocabulary as f64 / entries as f64,
+            })
+            .collect())
+    }
+}
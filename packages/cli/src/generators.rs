@@ -0,0 +1,159 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{Config, Database, IS_ANSWERED};
+
+pub const DEFAULT_GENERATOR: &str = "default";
+
+// (name, description, template)
+const BUILTINS: &[(&str, &str, &str)] = &[
+    (
+        DEFAULT_GENERATOR,
+        "One unexpected creative stimulus of any kind",
+        "Generate one unexpected creative stimulus - it could be anything: an object, a situation, a constraint, a weird fact, a made-up rule, or anything else that could spark ideas. Just give me the one thing, no explanation.",
+    ),
+    (
+        "assumption-breakers",
+        "A \"what if\" that challenges how the world works",
+        "Give me a \"what if\" scenario that challenges a common assumption about how the world works. Just the scenario, no elaboration.",
+    ),
+    (
+        "abstract-provocations",
+        "Open-ended prompts, poetic or philosophical",
+        "Generate one abstract creative prompt that could be interpreted in multiple ways - it might be poetic, philosophical, or just wonderfully weird.",
+    ),
+    (
+        "constraint-catalysts",
+        "A rule or limitation to work within",
+        "Give me one creative constraint or limitation that could spark interesting ideas. It could be a rule, a restriction, or an unusual requirement.",
+    ),
+];
+
+// A `[generators]` entry in config.toml: either just the template, or a
+// table with a description.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum GeneratorConfig {
+    Template(String),
+    Full {
+        template: String,
+        description: Option<String>,
+    },
+}
+
+// A named instruction for model-backed prompt providers.
+pub struct Generator {
+    pub name: String,
+    pub description: String,
+    pub template: String,
+    pub from_config: bool,
+}
+
+// Built-ins first, then those from config. A config generator with a
+// built-in's name replaces it, as does `default_prompt_template` for the
+// default generator.
+pub fn registry(config: &Config) -> Vec<Generator> {
+    let mut generators: Vec<Generator> = BUILTINS
+        .iter()
+        .map(|(name, description, template)| Generator {
+            name: name.to_string(),
+            description: description.to_string(),
+            template: template.to_string(),
+            from_config: false,
+        })
+        .collect();
+
+    if let Some(template) = &config.default_prompt_template {
+        generators[0].template = template.clone();
+        generators[0].from_config = true;
+    }
+
+    for (name, entry) in config.generators.iter().flatten() {
+        let (template, description) = match entry.clone() {
+            GeneratorConfig::Template(template) => (template, None),
+            GeneratorConfig::Full { template, description } => (template, description),
+        };
+        let generator = Generator {
+            name: name.clone(),
+            description: description.unwrap_or_default(),
+            template,
+            from_config: true,
+        };
+
+        match generators.iter_mut().find(|g| g.name == *name) {
+            Some(existing) => *existing = generator,
+            None => generators.push(generator),
+        }
+    }
+
+    generators
+}
+
+// The generator named on the command line, else `default_generator` from
+// config, else the default.
+pub fn resolve(config: &Config, name: Option<&str>) -> Result<Generator> {
+    let name = name
+        .or(config.default_generator.as_deref())
+        .unwrap_or(DEFAULT_GENERATOR);
+
+    let mut generators = registry(config);
+    match generators.iter().position(|g| g.name == name) {
+        Some(index) => Ok(generators.swap_remove(index)),
+        None => anyhow::bail!(
+            "No generator named '{}'. Available: {}",
+            name,
+            generators.iter().map(|g| g.name.as_str()).collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+pub fn print_generators(config: &Config) {
+    let default = config.default_generator.as_deref().unwrap_or(DEFAULT_GENERATOR);
+
+    for generator in registry(config) {
+        let marker = if generator.name == default { " (default)" } else { "" };
+        let source = if generator.from_config { " [config]" } else { "" };
+        println!("🎛️  {}{}{}", generator.name, marker, source);
+        if !generator.description.is_empty() {
+            println!("    {}", generator.description);
+        }
+    }
+    println!("\nUse one with: estrange receive --generator <name>");
+}
+
+pub struct GeneratorStats {
+    pub name: String,
+    pub entries: u32,
+    pub average_words: f64,
+    pub revised: u32,
+}
+
+impl Database {
+    // How responses differ by the generator that prompted them. Entries from
+    // before generators were recorded, or from non-model providers, are left out.
+    pub fn generator_stats(&self) -> Result<Vec<GeneratorStats>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT json_extract(p.metadata, '$.generator') AS generator,
+                    COUNT(*),
+                    AVG(word_count(p.response)),
+                    SUM(EXISTS (SELECT 1 FROM entry_revisions r WHERE r.entry_id = p.id))
+             FROM creative_prompts p
+             WHERE p.deleted_at IS NULL AND {} AND generator IS NOT NULL
+             GROUP BY generator
+             ORDER BY COUNT(*) DESC, generator ASC",
+            IS_ANSWERED
+        ))?;
+
+        let stats = stmt
+            .query_map([], |row| {
+                Ok(GeneratorStats {
+                    name: row.get(0)?,
+                    entries: row.get(1)?,
+                    average_words: row.get::<_, Option<f64>>(2)?.unwrap_or(0.0),
+                    revised: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(stats)
+    }
+}
@@ -0,0 +1,322 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::providers::{Prompt, PromptProvider, PromptRequest};
+use crate::rng::{stable_hash, SplitMix64};
+use crate::Database;
+
+const BUNDLED: &str = include_str!("../grammars/default.json");
+const START: &str = "origin";
+const MAX_DEPTH: usize = 32;
+
+// A Tracery-style grammar: each rule maps to the texts it can expand to.
+// Inside a text:
+//   #rule#               expands another rule
+//   #rule.a.capitalize#  applies modifiers (a, s, capitalize, capitalizeAll)
+//   [name:#rule#]        expands once and saves it, so later #name# repeats it
+// Grammar files are JSON or TOML with a rule per key; a rule can be one
+// string or a list. Expansion starts at `origin`.
+pub struct Grammar {
+    rules: HashMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RuleSource {
+    One(String),
+    Many(Vec<String>),
+}
+
+// One choice made while expanding: which option of which rule.
+#[derive(Serialize)]
+pub struct TraceStep {
+    pub rule: String,
+    pub choice: usize,
+}
+
+pub struct Expansion {
+    pub text: String,
+    pub trace: Vec<TraceStep>,
+}
+
+impl Grammar {
+    pub fn bundled() -> Result<Self> {
+        Self::parse(BUNDLED, "json").context("Bundled grammar is invalid")
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read grammar at {}", path.display()))?;
+        let format = path.extension().and_then(|ext| ext.to_str()).unwrap_or("json");
+        Self::parse(&content, format).with_context(|| format!("Failed to parse grammar at {}", path.display()))
+    }
+
+    fn parse(content: &str, format: &str) -> Result<Self> {
+        let sources: HashMap<String, RuleSource> = match format {
+            "toml" => toml::from_str(content)?,
+            _ => serde_json::from_str(content)?,
+        };
+
+        let rules: HashMap<String, Vec<String>> = sources
+            .into_iter()
+            .map(|(name, source)| match source {
+                RuleSource::One(text) => (name, vec![text]),
+                RuleSource::Many(texts) => (name, texts),
+            })
+            .collect();
+
+        match rules.get(START) {
+            Some(options) if !options.is_empty() => Ok(Grammar { rules }),
+            _ => anyhow::bail!("Grammar needs a non-empty `{}` rule to start from", START),
+        }
+    }
+
+    // Expand `origin`. The same seed always gives the same text and trace.
+    pub fn expand(&self, seed: u64) -> Result<Expansion> {
+        let mut expander = Expander {
+            grammar: self,
+            rng: SplitMix64::new(seed),
+            saved: HashMap::new(),
+            trace: Vec::new(),
+            depth: 0,
+        };
+        let text = expander.expand_rule(START)?;
+        Ok(Expansion { text, trace: expander.trace })
+    }
+}
+
+struct Expander<'a> {
+    grammar: &'a Grammar,
+    rng: SplitMix64,
+    saved: HashMap<String, String>,
+    trace: Vec<TraceStep>,
+    depth: usize,
+}
+
+impl Expander<'_> {
+    fn expand_rule(&mut self, rule: &str) -> Result<String> {
+        if let Some(saved) = self.saved.get(rule) {
+            return Ok(saved.clone());
+        }
+
+        let options = self
+            .grammar
+            .rules
+            .get(rule)
+            .with_context(|| format!("Grammar has no rule named `{}`", rule))?;
+        if options.is_empty() {
+            anyhow::bail!("Grammar rule `{}` has no options", rule);
+        }
+
+        let choice = self.rng.below(options.len());
+        self.trace.push(TraceStep { rule: rule.to_string(), choice });
+
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            anyhow::bail!("Grammar recursed more than {} levels deep at `{}`", MAX_DEPTH, rule);
+        }
+        let text = self.expand_text(&options[choice]);
+        self.depth -= 1;
+        text
+    }
+
+    fn expand_text(&mut self, text: &str) -> Result<String> {
+        let mut output = String::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find(['#', '[']) {
+            output.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+
+            if rest[start..].starts_with('[') {
+                // Actions may contain #tags# but not nested brackets
+                let end = after.find(']').with_context(|| format!("Unclosed [ in `{}`", text))?;
+                self.apply_action(&after[..end])?;
+                rest = &after[end + 1..];
+            } else {
+                let end = after.find('#').with_context(|| format!("Unclosed # in `{}`", text))?;
+                output.push_str(&self.expand_tag(&after[..end])?);
+                rest = &after[end + 1..];
+            }
+        }
+
+        output.push_str(rest);
+        Ok(output)
+    }
+
+    // `name:#rule#` - expand now, and make #name# give the same text later.
+    fn apply_action(&mut self, action: &str) -> Result<()> {
+        let (name, value) = action
+            .split_once(':')
+            .with_context(|| format!("Grammar action `[{}]` should look like [name:#rule#]", action))?;
+        let value = self.expand_text(value)?;
+        self.saved.insert(name.trim().to_string(), value);
+        Ok(())
+    }
+
+    fn expand_tag(&mut self, tag: &str) -> Result<String> {
+        let mut parts = tag.split('.');
+        let rule = parts.next().unwrap_or_default();
+        let mut text = self.expand_rule(rule)?;
+
+        for modifier in parts {
+            text = match modifier {
+                "a" => with_article(&text),
+                "s" => plural(&text),
+                "capitalize" => capitalize(&text),
+                "capitalizeAll" => text.split(' ').map(capitalize).collect::<Vec<_>>().join(" "),
+                other => anyhow::bail!("Unknown grammar modifier `.{}` in #{}#", other, tag),
+            };
+        }
+        Ok(text)
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn with_article(text: &str) -> String {
+    let vowel = text.chars().next().is_some_and(|c| "aeiouAEIOU".contains(c));
+    format!("{} {}", if vowel { "an" } else { "a" }, text)
+}
+
+fn plural(text: &str) -> String {
+    let consonant_y = text.ends_with('y') && !text.ends_with("ay") && !text.ends_with("ey") && !text.ends_with("oy");
+    if consonant_y {
+        format!("{}ies", &text[..text.len() - 1])
+    } else if ["s", "x", "ch", "sh"].iter().any(|end| text.ends_with(end)) {
+        format!("{}es", text)
+    } else {
+        format!("{}s", text)
+    }
+}
+
+// The seed for a day's expansion: the journal's seed mixed with the date, so
+// every journal gets its own sequence but the same day always repeats.
+pub fn day_seed(journal_seed: &str, day: chrono::NaiveDate) -> u64 {
+    stable_hash(&format!("{}:grammar:{}", journal_seed, day))
+}
+
+// Generates prompts from a grammar file, or the bundled grammar.
+pub struct GrammarProvider {
+    path: Option<PathBuf>,
+    journal_seed: String,
+}
+
+impl GrammarProvider {
+    pub fn new(path: Option<PathBuf>, db: &Database) -> Result<Self> {
+        Ok(GrammarProvider {
+            path,
+            journal_seed: db.journal_seed()?,
+        })
+    }
+}
+
+pub fn load(path: Option<&Path>) -> Result<Grammar> {
+    match path {
+        Some(path) => Grammar::load(path),
+        None => Grammar::bundled(),
+    }
+}
+
+#[async_trait]
+impl PromptProvider for GrammarProvider {
+    fn name(&self) -> String {
+        match &self.path {
+            Some(path) => format!("grammar {}", path.display()),
+            None => "grammar".to_string(),
+        }
+    }
+
+    async fn generate(&self, request: &PromptRequest) -> Result<Prompt> {
+        let grammar = load(self.path.as_deref())?;
+        let seed = day_seed(&self.journal_seed, request.day);
+        let expansion = grammar.expand(seed)?;
+
+        let source = match &self.path {
+            Some(path) => path.display().to_string(),
+            None => "bundled".to_string(),
+        };
+
+        // The seed reproduces the prompt with `estrange grammar --seed`; the
+        // trace shows which option each rule picked.
+        Ok(Prompt::new(expansion.text, "grammar")
+            .with("grammar", source)
+            .with("grammar_seed", seed.to_string())
+            .with("expansion_trace", serde_json::to_value(&expansion.trace)?))
+    }
+
+    // Expanding once catches missing rules as well as parse errors
+    async fn check(&self) -> Result<String> {
+        let grammar = load(self.path.as_deref())?;
+        grammar.expand(0)?;
+        Ok(format!("{} rules", grammar.rules.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_expansion() {
+        let grammar = Grammar::bundled().unwrap();
+        let first = grammar.expand(42).unwrap();
+        let second = grammar.expand(42).unwrap();
+
+        assert_eq!(first.text, second.text);
+        assert_eq!(
+            serde_json::to_value(&first.trace).unwrap(),
+            serde_json::to_value(&second.trace).unwrap()
+        );
+        assert_eq!(first.trace[0].rule, START);
+    }
+
+    #[test]
+    fn seeds_vary_the_expansion() {
+        let grammar = Grammar::bundled().unwrap();
+        let texts: std::collections::HashSet<String> = (0..20).map(|seed| grammar.expand(seed).unwrap().text).collect();
+        assert!(texts.len() > 1);
+    }
+
+    #[test]
+    fn applies_modifiers_and_saved_actions() {
+        let grammar = Grammar::parse(
+            r#"{"origin": "[hero:#name#]#hero.capitalize# met #animal.a#; #hero# kept two #animal.s#", "name": "ada", "animal": "owl"}"#,
+            "json",
+        )
+        .unwrap();
+        assert_eq!(grammar.expand(7).unwrap().text, "Ada met an owl; ada kept two owls");
+    }
+
+    #[test]
+    fn reads_toml_grammars() {
+        let grammar = Grammar::parse("origin = [\"#thing#\"]\nthing = \"a door\"", "toml").unwrap();
+        assert_eq!(grammar.expand(0).unwrap().text, "a door");
+    }
+
+    #[test]
+    fn stops_runaway_recursion() {
+        let grammar = Grammar::parse(r##"{"origin": "#loop#", "loop": "again #loop#"}"##, "json").unwrap();
+        let error = grammar.expand(0).err().unwrap();
+        assert!(error.to_string().contains(&format!("more than {} levels deep", MAX_DEPTH)));
+    }
+
+    #[test]
+    fn reports_missing_rules_and_origin() {
+        let grammar = Grammar::parse(r##"{"origin": "#nowhere#"}"##, "json").unwrap();
+        assert!(grammar.expand(0).is_err());
+
+        assert!(Grammar::parse(r#"{"start": "hello"}"#, "json").is_err());
+        assert!(Grammar::parse(r#"{"origin": []}"#, "json").is_err());
+    }
+}
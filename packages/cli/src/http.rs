@@ -0,0 +1,135 @@
+use anyhow::{Context, Result};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::user_agent::build_user_agent;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const BASE_DELAY: Duration = Duration::from_millis(500);
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+// The `[http]` section of config.toml. Without `proxy`, the usual
+// HTTP_PROXY / HTTPS_PROXY / NO_PROXY variables apply.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct HttpConfig {
+    // Seconds a whole request may take
+    pub timeout_secs: Option<u64>,
+    pub connect_timeout_secs: Option<u64>,
+    // Extra attempts after a 429, a 5xx, or a failed connection
+    pub max_retries: Option<u32>,
+    // e.g. "http://proxy.internal:3128", used for every request
+    pub proxy: Option<String>,
+    // Comma-separated hosts that skip `proxy`
+    pub no_proxy: Option<String>,
+}
+
+pub fn user_agent() -> String {
+    build_user_agent("cli", env!("CARGO_PKG_VERSION"))
+}
+
+// Every outgoing request goes through one of these, so they all get the
+// same timeouts, proxy, user agent and retries. Cloning is cheap.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: Client,
+    max_retries: u32,
+}
+
+impl HttpClient {
+    pub fn new(config: Option<&HttpConfig>) -> Result<Self> {
+        let config = config.cloned().unwrap_or_default();
+
+        let mut builder = Client::builder()
+            .user_agent(user_agent())
+            .timeout(Duration::from_secs(config.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS)))
+            .connect_timeout(Duration::from_secs(
+                config.connect_timeout_secs.unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS),
+            ));
+
+        if let Some(proxy) = &config.proxy {
+            let proxy = reqwest::Proxy::all(proxy)
+                .with_context(|| format!("Invalid proxy URL in [http] config: {}", proxy))?
+                .no_proxy(config.no_proxy.as_deref().and_then(reqwest::NoProxy::from_string));
+            builder = builder.proxy(proxy);
+        }
+
+        Ok(HttpClient {
+            client: builder.build().context("Failed to set up the HTTP client")?,
+            max_retries: config.max_retries.unwrap_or(DEFAULT_MAX_RETRIES),
+        })
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.client.post(url)
+    }
+
+    // For libraries that build their own `reqwest::Request` from a client
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    // Send `request`, retrying rate limits, server errors and failed
+    // connections with exponential backoff (or the server's Retry-After).
+    // Other responses, errors included, are returned for the caller to read.
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            // The last attempt sends the original. Requests with streaming
+            // bodies can't be copied, so they only get that one.
+            let current = match request.try_clone() {
+                Some(current) if attempt < self.max_retries => current,
+                _ => return Ok(request.send().await?),
+            };
+
+            let (delay, reason) = match current.send().await {
+                Ok(response) if is_retryable(response.status()) => {
+                    (retry_after(&response).unwrap_or_else(|| backoff(attempt)), response.status().to_string())
+                }
+                Ok(response) => return Ok(response),
+                Err(e) if e.is_timeout() || e.is_connect() => (backoff(attempt), without_url(&e)),
+                Err(e) => return Err(e.into()),
+            };
+
+            attempt += 1;
+            eprintln!(
+                "⏳ {} - retrying in {:.1}s ({}/{})",
+                reason,
+                delay.as_secs_f64(),
+                attempt,
+                self.max_retries
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+// 0.5s, 1s, 2s, 4s... capped at MAX_DELAY
+fn backoff(attempt: u32) -> Duration {
+    BASE_DELAY.saturating_mul(2u32.saturating_pow(attempt)).min(MAX_DELAY)
+}
+
+// Retry-After in seconds; the HTTP-date form is rare enough to fall back
+// to our own backoff.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let seconds: u64 = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.parse().ok()?;
+    Some(Duration::from_secs(seconds).min(MAX_DELAY))
+}
+
+fn without_url(error: &reqwest::Error) -> String {
+    let kind = if error.is_timeout() { "Timed out" } else { "Connection failed" };
+    match error.url().and_then(|url| url.host_str()) {
+        Some(host) => format!("{} reaching {}", kind, host),
+        None => kind.to_string(),
+    }
+}
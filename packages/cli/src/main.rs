@@ -1,21 +1,42 @@
 
 use chrono::Utc;
-// Add these imports at the top
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
-use rusqlite::{Connection, params};
-use tempfile::NamedTempFile;
-use std::env;
-use std::io::Write;
+use rusqlite::{Connection, OptionalExtension, params};
+use std::collections::BTreeMap;
+use std::io::{IsTerminal, Write};
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
+use std::path::{Path, PathBuf};
 
 use crate::metrics::{init_metrics, metrics, send_metrics_to_grafana, MetricsConfig, SessionTimer};
 
+mod archive;
+mod calendar;
+mod config;
+mod daily;
+mod deck;
+mod doctor;
+mod drafts;
+mod editor;
+mod gemini;
+mod generation;
+mod generators;
+mod grammar;
+mod http;
 mod metrics;
+mod migrations;
+mod openai;
+mod providers;
+mod query;
+mod revisions;
+mod rng;
+mod search;
+mod secrets;
+mod similarity;
+mod tags;
+mod template;
+mod tombstones;
 mod user_agent;
 
 
@@ -27,6 +48,9 @@ struct Cli {
     command: Option<Commands>,
     #[arg(short, long)]
     manual: bool,
+    /// Config file to read on top of the default one (and $ESTRANGE_CONFIG)
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -37,6 +61,12 @@ enum Commands {
         /// Enter your own prompt instead of receiving a generated one
         #[arg(short, long)]
         manual: bool,
+        /// Tag today's entry (repeatable)
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+        /// Prompt generator to use (see `estrange generators`)
+        #[arg(short, long)]
+        generator: Option<String>,
     },
     /// Browse your creative journey
     #[command(name = "retrace", alias = "list")]
@@ -44,156 +74,246 @@ enum Commands {
         /// Number of entries to show (default: 10)
         #[arg(short, long, default_value = "10")]
         limit: u32,
+        /// Only show entries carrying this tag (repeatable)
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
     },
     /// Search through your responses for patterns or content
     #[command(name = "excavate", alias = "search")]
     Excavate {
-        /// Search term
+        /// Search query: words, "exact phrases", prefix*, OR, -negation, and
+        /// prompt:, response:, tag:, before:, after:, words>N filters. OR
+        /// joins only its neighbours: a OR b c means (a OR b) AND c
         query: String,
+        /// Only search entries carrying this tag (repeatable)
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
     },
     /// Reflect on your creative patterns and growth
     #[command(name = "witness", alias = "stats")]
     Witness,
     /// Preserve your creative journey
     #[command(name = "archive", alias = "export")]
-    Archive,
-}
-
-// Configuration structure
-#[derive(Serialize, Deserialize, Default)]
-struct Config {
-    gemini_api_key: Option<String>,
-    default_prompt_template: Option<String>,
-    metrics: Option<MetricsConfig>
-}
-
-impl Config {
-    fn load() -> Result<Self> {
-        let config_path = get_config_path()?;
-
-        if !config_path.exists() {
-            anyhow::bail!(
-                "Config file not found. Copy config.example.toml to config.toml and add your API key."
-            );
-        }
-
-        let config_content = fs::read_to_string(&config_path)
-            .with_context(|| format!("Failed to read config file at {}", config_path.display()))?;
-
-        let config: Config = toml::from_str(&config_content)
-            .with_context(|| "Failed to parse config file - ensure it's valid TOML")?;
-
-        Ok(config)
-    }
-}
-
-fn get_config_path() -> Result<PathBuf> {
-    let mut path = env::current_dir()
-        .context("Could not get current directory")?;
-
-    path.push("config.toml");
-    Ok(path)
-}
-
-// API structures for Gemini
-#[derive(Serialize)]
-struct GeminiRequest {
-    contents: Vec<GeminiContent>,
-}
-
-#[derive(Serialize)]
-struct GeminiContent {
-    parts: Vec<GeminiPart>,
+    Archive {
+        /// Print the JSON Schema describing the archive format instead
+        #[arg(long)]
+        schema: bool,
+    },
+    /// Restore entries from an `archive` export
+    #[command(name = "import")]
+    Import {
+        /// Archive file to read (use - for stdin)
+        path: PathBuf,
+        /// Show what would be imported without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Reopen an entry's response in your editor, keeping the old version
+    #[command(name = "revise")]
+    Revise {
+        /// Entry ID (see `estrange retrace`)
+        id: i64,
+    },
+    /// Show how an entry's response changed across revisions
+    #[command(name = "history")]
+    History {
+        /// Entry ID (see `estrange retrace`)
+        id: i64,
+    },
+    /// Forget an entry (restorable for 30 days)
+    #[command(name = "forget")]
+    Forget {
+        /// Entry ID (see `estrange retrace`)
+        id: i64,
+    },
+    /// Bring back a forgotten entry
+    #[command(name = "restore")]
+    Restore {
+        /// Entry ID given when it was forgotten
+        id: i64,
+    },
+    /// Map the creative territories of your entries
+    #[command(name = "tag")]
+    Tag {
+        #[command(subcommand)]
+        action: TagAction,
+    },
+    /// Manage the prompt decks used when no other prompt source answers
+    #[command(name = "deck")]
+    Deck {
+        #[command(subcommand)]
+        action: DeckAction,
+    },
+    /// List the named prompt generators
+    #[command(name = "generators")]
+    Generators,
+    /// Expand a prompt grammar, to preview it or reproduce a past prompt
+    #[command(name = "grammar")]
+    Grammar {
+        /// Grammar file (JSON or TOML); defaults to the bundled grammar
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+        /// Expand with this seed (from an entry's `grammar_seed`)
+        #[arg(short, long)]
+        seed: Option<u64>,
+        /// How many prompts to generate with fresh seeds
+        #[arg(short = 'n', long, default_value = "5")]
+        count: usize,
+        /// Show which option each rule picked
+        #[arg(long)]
+        trace: bool,
+    },
+    /// Show unfinished responses, or discard abandoned ones
+    #[command(name = "drafts")]
+    Drafts {
+        #[command(subcommand)]
+        action: Option<DraftAction>,
+    },
+    /// Create, read and change config.toml
+    #[command(name = "config")]
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Check your config, journal, editor, prompt providers and metrics
+    #[command(name = "doctor")]
+    Doctor,
+    /// Keep API keys and tokens in the system keyring instead of config.toml
+    #[command(name = "secret")]
+    Secret {
+        #[command(subcommand)]
+        action: SecretAction,
+    },
 }
 
-#[derive(Serialize)]
-struct GeminiPart {
-    text: String,
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Write a commented starter config to the default location
+    Init {
+        /// Replace an existing file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Print a setting, e.g. `estrange config get generation.temperature`
+    Get { key: String },
+    /// Change a setting, keeping the file's comments
+    Set { key: String, value: String },
+    /// Show which files the config is read from
+    Path,
 }
 
-#[derive(Deserialize)]
-struct GeminiResponse {
-    candidates: Vec<GeminiCandidate>,
+#[derive(Subcommand)]
+enum SecretAction {
+    /// Store a secret, read from stdin, e.g. `estrange secret set gemini`
+    Set {
+        /// Name to store it under: `gemini` or `service/account`
+        name: String,
+    },
+    /// Delete a stored secret
+    #[command(alias = "remove")]
+    Rm { name: String },
 }
 
-#[derive(Deserialize)]
-struct GeminiCandidate {
-    content: GeminiResponseContent,
+#[derive(Subcommand)]
+enum DeckAction {
+    /// Add a prompt to one of your decks
+    Add {
+        /// The prompt text
+        text: String,
+        /// Tag the prompt (repeatable)
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+        /// Deck to add to (default: mine)
+        #[arg(short, long)]
+        deck: Option<String>,
+    },
+    /// List decks and how many of their prompts you have drawn
+    #[command(alias = "ls")]
+    List {
+        /// Show every prompt, marking the ones already drawn
+        #[arg(short, long)]
+        prompts: bool,
+    },
+    /// Import a deck file (TOML, JSON, or one prompt per line)
+    Import {
+        /// Deck file to read
+        path: PathBuf,
+        /// Name for the deck (default: the file name)
+        #[arg(short, long)]
+        name: Option<String>,
+    },
 }
 
-#[derive(Deserialize)]
-struct GeminiResponseContent {
-    parts: Vec<GeminiResponsePart>,
+#[derive(Subcommand)]
+enum DraftAction {
+    /// List drafts and the entries they belong to (the default)
+    #[command(alias = "ls")]
+    List,
+    /// Delete an entry's draft, or every abandoned draft when no ID is given
+    #[command(alias = "rm")]
+    Discard {
+        /// Entry ID (see `estrange drafts`)
+        id: Option<i64>,
+    },
 }
 
-#[derive(Deserialize)]
-struct GeminiResponsePart {
-    text: String,
+#[derive(Subcommand)]
+enum TagAction {
+    /// Add tags to an entry
+    Add {
+        /// Entry ID (see `estrange retrace`)
+        id: i64,
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Remove tags from an entry
+    #[command(alias = "rm")]
+    Remove {
+        /// Entry ID (see `estrange retrace`)
+        id: i64,
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// List an entry's tags, or every tag in your journal
+    #[command(alias = "ls")]
+    List {
+        /// Entry ID; omit to list all tags with counts
+        id: Option<i64>,
+    },
 }
 
-// API client
-struct GeminiClient {
-    client: Client,
-    api_key: String,
+// Configuration structure
+#[derive(Serialize, Deserialize, Default)]
+struct Config {
+    gemini_api_key: Option<String>,
+    // Model for Gemini providers that don't name one
+    gemini_model: Option<String>,
+    // Temperature, top_p and system instruction for model-backed providers
+    generation: Option<generation::GenerationConfig>,
+    default_prompt_template: Option<String>,
+    metrics: Option<MetricsConfig>,
+    // IANA name, e.g. "America/Los_Angeles". Defaults to the system zone.
+    timezone: Option<String>,
+    // Hour (0-23) at which a new journal day begins. Defaults to midnight.
+    day_starts_at: Option<u32>,
+    // Where the prompt generator worker publishes the shared daily prompt
+    daily_prompt_url: Option<String>,
+    // Named prompt templates, added to (or replacing) the built-in ones
+    generators: Option<BTreeMap<String, generators::GeneratorConfig>>,
+    // Generator used when `--generator` isn't given
+    default_generator: Option<String>,
+    // Ordered fallback chain of prompt sources. Defaults to Gemini when an
+    // API key is set, otherwise the daily prompt.
+    providers: Option<Vec<providers::ProviderConfig>>,
+    // How alike (0-1) a new prompt may be to a past one before it's
+    // rejected as a repeat
+    similarity_threshold: Option<f64>,
+    // Timeouts, retries and proxy for every request the CLI makes
+    http: Option<http::HttpConfig>,
+    // Editor command (after $VISUAL and $EDITOR) and response file extension
+    editor: Option<editor::EditorConfig>,
 }
 
-impl GeminiClient {
-    fn new() -> Result<Self> {
-        let config = Config::load()?;
-
-        let api_key = config.gemini_api_key
-            .or_else(|| env::var("GEMINI_API_KEY").ok())
-            .context("No Gemini API key found. Set one with: estrange config --set-api-key YOUR_KEY")?;
-
-        Ok(GeminiClient {
-            client: Client::new(),
-            api_key,
-        })
-    }
-
-    async fn generate_prompt(&self, prompt_template: &str) -> Result<String> {
-        let request = GeminiRequest {
-            contents: vec![GeminiContent {
-                parts: vec![GeminiPart {
-                    text: prompt_template.to_string(),
-                }],
-            }],
-        };
-
-        let url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.5-flash:generateContent?key={}",
-            self.api_key
-        );
-
-        let response = self
-            .client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to send request to Gemini API")?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            anyhow::bail!("Gemini API error: {}", error_text);
-        }
-
-        let gemini_response: GeminiResponse = response
-            .json()
-            .await
-            .context("Failed to parse Gemini API response")?;
-
-        let generated_text = gemini_response
-            .candidates
-            .first()
-            .and_then(|c| c.content.parts.first())
-            .map(|p| p.text.trim().to_string())
-            .context("No content in Gemini API response")?;
-
-        Ok(generated_text)
-    }
-}
 
 // Update your main function to handle the default behavior and new commands
 #[tokio::main]
@@ -201,7 +321,31 @@ async fn main() -> Result<()> {
     init_metrics()?;
 
     let cli = Cli::parse();
-    let db = Database::new()?;
+
+    // Config commands run before the config is loaded, so they can fix a broken one
+    if let Some(Commands::Config { action }) = &cli.command {
+        if let Some(m) = metrics() {
+            m.command_used("config");
+        }
+        return run_config_command(cli.config.as_deref(), action);
+    }
+    // Secrets too, since the config may refer to one that isn't stored yet
+    if let Some(Commands::Secret { action }) = &cli.command {
+        if let Some(m) = metrics() {
+            m.command_used("secret");
+        }
+        return run_secret_command(action);
+    }
+    // As does the doctor, so a broken config is diagnosed rather than fatal
+    if let Some(Commands::Doctor) = &cli.command {
+        if let Some(m) = metrics() {
+            m.command_used("doctor");
+        }
+        return doctor::run(cli.config.as_deref()).await;
+    }
+
+    let config = Config::load(cli.config.as_deref())?;
+    let db = Database::new(calendar::Calendar::from_config(&config)?)?;
 
     if let Err(e) = db.update_metrics() {
             eprintln!("Warning: Failed to update metrics: {}", e);
@@ -213,21 +357,21 @@ async fn main() -> Result<()> {
             if let Some( m) = metrics() {
                 m.command_used("receive");
             }
-            receive_and_respond(&db, cli.manual).await?;
+            receive_and_respond(&db, &config, cli.manual, None, &[]).await?;
         }
 
-        Some(Commands::Receive { manual }) => {
+        Some(Commands::Receive { manual, tags, generator }) => {
             if let Some( m) = metrics() {
                 m.command_used("receive");
             }
-            receive_and_respond(&db, manual || cli.manual).await?;
+            receive_and_respond(&db, &config, manual || cli.manual, generator.as_deref(), &tags).await?;
         }
 
-        Some(Commands::Retrace { limit }) => {
+        Some(Commands::Retrace { limit, tags }) => {
             if let Some( m) = metrics() {
                 m.command_used("retrace");
             }
-            let entries = db.list_entries(limit)?;
+            let entries = db.list_entries(limit, &tags)?;
 
             if entries.is_empty() {
                 println!("🌱 No creative journeys yet. Run 'estrange' to begin your first disruption!");
@@ -240,21 +384,25 @@ async fn main() -> Result<()> {
             }
         }
 
-        Some(Commands::Excavate { query }) => {
+        Some(Commands::Excavate { query, tags }) => {
             if let Some(m) = metrics() {
                 m.command_used("excavate");
                 m.search_performed();
             }
-            let entries = db.search_entries(&query)?;
+            let mut compiled = query::compile(&query)?;
+            for tag in &tags {
+                compiled.require_tag(tag);
+            }
+            let hits = db.search_entries(&compiled)?;
 
-            if entries.is_empty() {
+            if hits.is_empty() {
                 println!("🔍 No entries found matching '{}'", query);
                 return Ok(());
             }
 
-            println!("🔍 Excavated {} entries matching '{}':\n", entries.len(), query);
-            for entry in entries {
-                print_entry(&entry);
+            println!("🔍 Excavated {} entries matching '{}':\n", hits.len(), query);
+            for hit in hits {
+                search::print_hit(&hit);
             }
         }
 
@@ -270,6 +418,48 @@ async fn main() -> Result<()> {
             println!("First estrangement: {}", first);
             println!("Most recent: {}", last);
 
+            let tag_counts = db.tag_counts()?;
+            if !tag_counts.is_empty() {
+                println!("\n🗺️  Creative territories:");
+                for (tag, count) in tag_counts.iter().take(10) {
+                    println!("  #{:<24} {}", tag, count);
+                }
+                if tag_counts.len() > 10 {
+                    println!("  …and {} more (estrange tag list)", tag_counts.len() - 10);
+                }
+            }
+
+            let generator_stats = db.generator_stats()?;
+            if generator_stats.len() > 1 {
+                println!("\n🎛️  Generators compared:");
+                println!("  {:<24} {:>7} {:>10} {:>8}", "", "entries", "avg words", "revised");
+                for stats in &generator_stats {
+                    println!(
+                        "  {:<24} {:>7} {:>10.0} {:>7.0}%",
+                        stats.name,
+                        stats.entries,
+                        stats.average_words,
+                        100.0 * stats.revised as f64 / stats.entries as f64
+                    );
+                }
+            }
+
+            let temperature_stats = db.temperature_stats()?;
+            if temperature_stats.len() > 1 {
+                println!("\n🌡️  Prompt temperature and your responses:");
+                println!("  {:<24} {:>7} {:>10} {:>15}", "", "entries", "avg words", "distinct words");
+                for stats in &temperature_stats {
+                    let label = match stats.temperature {
+                        Some(temperature) => format!("{:.2}", temperature),
+                        None => "model default".to_string(),
+                    };
+                    println!(
+                        "  {:<24} {:>7} {:>10.0} {:>15.0}",
+                        label, stats.entries, stats.average_words, stats.average_vocabulary
+                    );
+                }
+            }
+
             if total > 0 {
                 println!("\n✨ Each disruption shapes your creative consciousness.");
             } else {
@@ -277,7 +467,11 @@ async fn main() -> Result<()> {
             }
         }
 
-        Some(Commands::Archive) => {
+        Some(Commands::Archive { schema }) => {
+            if schema {
+                print!("{}", archive::SCHEMA);
+                return Ok(());
+            }
             if let Some(m) = metrics() {
                 m.command_used("archive");
                 m.archive_exported();
@@ -285,9 +479,185 @@ async fn main() -> Result<()> {
             let json = db.export_all()?;
             println!("{}", json);
         }
+
+        Some(Commands::Import { path, dry_run }) => {
+            if let Some(m) = metrics() {
+                m.command_used("import");
+            }
+            let (source, json) = if path.as_os_str() == "-" {
+                ("stdin".to_string(), std::io::read_to_string(std::io::stdin())?)
+            } else {
+                let json = fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read archive at {}", path.display()))?;
+                (path.display().to_string(), json)
+            };
+
+            let report = db.import_archive(&json, dry_run)?;
+
+            if dry_run {
+                println!("🔎 Dry run - nothing was written. Importing {} would:", source);
+            } else {
+                println!("📥 Imported {}:", source);
+            }
+            println!("  ✓ {} added", report.added);
+            println!("  ↷ {} skipped (already in your journal)", report.skipped);
+            if report.forgotten > 0 {
+                println!("  🍂 {} forgotten (deleted on another device)", report.forgotten);
+            }
+            if !report.conflicts.is_empty() {
+                println!("  ⚠ {} in conflict (local version kept):", report.conflicts.len());
+                for conflict in &report.conflicts {
+                    println!("      {}", conflict);
+                }
+            }
+
+            if !dry_run {
+                if let Err(e) = db.update_metrics() {
+                    eprintln!("Warning: Failed to update metrics: {}", e);
+                }
+            }
+        }
+
+        Some(Commands::Revise { id }) => {
+            if let Some(m) = metrics() {
+                m.command_used("revise");
+            }
+            revisions::revise(&db, &editor::Editor::from_config(&config)?, id)?;
+        }
+
+        Some(Commands::History { id }) => {
+            if let Some(m) = metrics() {
+                m.command_used("history");
+            }
+            revisions::print_history(&db, id)?;
+        }
+
+        Some(Commands::Forget { id }) => {
+            if let Some(m) = metrics() {
+                m.command_used("forget");
+            }
+            db.forget_entry(id)?;
+            println!(
+                "🍂 Entry {} forgotten. Changed your mind? 'estrange restore {}' within {} days",
+                id, id, tombstones::UNDO_WINDOW_DAYS
+            );
+            if let Err(e) = db.update_metrics() {
+                eprintln!("Warning: Failed to update metrics: {}", e);
+            }
+        }
+
+        Some(Commands::Restore { id }) => {
+            if let Some(m) = metrics() {
+                m.command_used("restore");
+            }
+            db.restore_entry(id)?;
+            println!("🌱 Entry {} restored", id);
+            if let Err(e) = db.update_metrics() {
+                eprintln!("Warning: Failed to update metrics: {}", e);
+            }
+        }
+
+        Some(Commands::Tag { action }) => {
+            if let Some(m) = metrics() {
+                m.command_used("tag");
+            }
+            match action {
+                TagAction::Add { id, tags } => {
+                    let tags = db.add_tags(id, &tags)?;
+                    println!("🏷️  Entry {} now carries: {}", id, tags::format_tags(&tags));
+                }
+                TagAction::Remove { id, tags } => {
+                    let tags = db.remove_tags(id, &tags)?;
+                    if tags.is_empty() {
+                        println!("🏷️  Entry {} has no tags left", id);
+                    } else {
+                        println!("🏷️  Entry {} now carries: {}", id, tags::format_tags(&tags));
+                    }
+                }
+                TagAction::List { id: Some(id) } => {
+                    let tags = db.entry_tags(id)?;
+                    if tags.is_empty() {
+                        println!("🏷️  Entry {} has no tags yet. Add some with 'estrange tag add {} <tag>'", id, id);
+                    } else {
+                        println!("{}", tags::format_tags(&tags));
+                    }
+                }
+                TagAction::List { id: None } => {
+                    let tag_counts = db.tag_counts()?;
+                    if tag_counts.is_empty() {
+                        println!("🏷️  No tags yet. Add some with 'estrange tag add <id> <tag>'");
+                    }
+                    for (tag, count) in tag_counts {
+                        println!("#{:<24} {}", tag, count);
+                    }
+                }
+            }
+        }
+
+        Some(Commands::Deck { action }) => {
+            if let Some(m) = metrics() {
+                m.command_used("deck");
+            }
+            match action {
+                DeckAction::Add { text, tags, deck: name } => {
+                    let path = deck::add_card(&text, &tags, name.as_deref())?;
+                    println!("🃏 Added to {}", path.display());
+                }
+                DeckAction::List { prompts } => deck::print_decks(&db, prompts)?,
+                DeckAction::Import { path, name } => {
+                    let (dest, added) = deck::import_deck(&path, name.as_deref())?;
+                    println!("🃏 Imported {} new prompt(s) into {}", added, dest.display());
+                }
+            }
+        }
+
+        Some(Commands::Generators) => {
+            if let Some(m) = metrics() {
+                m.command_used("generators");
+            }
+            generators::print_generators(&config);
+        }
+
+        Some(Commands::Grammar { file, seed, count, trace }) => {
+            if let Some(m) = metrics() {
+                m.command_used("grammar");
+            }
+            let grammar = grammar::load(file.as_deref())?;
+            let seeds: Vec<u64> = match seed {
+                Some(seed) => vec![seed],
+                None => {
+                    let now = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+                    (0..count).map(|i| rng::stable_hash(&format!("{}:{}", now, i))).collect()
+                }
+            };
+
+            for seed in seeds {
+                let expansion = grammar.expand(seed)?;
+                println!("🎲 {:<20} {}", seed, expansion.text);
+                if trace {
+                    for step in &expansion.trace {
+                        println!("     {} → option {}", step.rule, step.choice);
+                    }
+                }
+            }
+        }
+
+        Some(Commands::Drafts { action }) => {
+            if let Some(m) = metrics() {
+                m.command_used("drafts");
+            }
+            match action.unwrap_or(DraftAction::List) {
+                DraftAction::List => drafts::print_drafts(&db)?,
+                DraftAction::Discard { id } => drafts::discard_drafts(&db, id)?,
+            }
+        }
+
+        Some(Commands::Config { .. } | Commands::Doctor | Commands::Secret { .. }) => {
+            unreachable!("config, doctor and secret commands run before the config is loaded")
+        }
     }
 
-    if let Err(e) = send_metrics_to_grafana().await {
+    if let Err(e) = send_metrics_to_grafana(&config).await {
             eprintln!("Warning: Failed to send metrics: {}", e);
         }
 
@@ -297,16 +667,46 @@ async fn main() -> Result<()> {
 struct CreativityEntry {
     id: i64,
     prompt: String,
-    response: String,
+    // None for sketch-only entries from the web app, and for today's prompt
+    // while it still awaits a response
+    response: Option<String>,
     created_at: String,
+    tags: Vec<String>,
+    // Questions the prompt arrived with, from providers that offer them
+    follow_ups: Vec<String>,
+    pending: bool,
+}
+
+// Columns read by `CreativityEntry::from_row`, for queries over `creative_prompts p`.
+const ENTRY_COLUMNS: &str =
+    "p.id, p.prompt, p.response, p.created_at, p.metadata, p.response IS NULL AND p.drawing_vector IS NULL";
+
+// A prompt counts as answered once it has a written or sketched response.
+// Entries without either are today's prompt, received but not yet answered.
+const IS_ANSWERED: &str = "(p.response IS NOT NULL OR p.drawing_vector IS NOT NULL)";
+
+impl CreativityEntry {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        let metadata: Option<String> = row.get(4)?;
+        Ok(CreativityEntry {
+            id: row.get(0)?,
+            prompt: row.get(1)?,
+            response: row.get(2)?,
+            created_at: row.get(3)?,
+            tags: tags::tags_from_metadata(metadata.as_deref()),
+            follow_ups: providers::follow_ups_from_metadata(metadata.as_deref()),
+            pending: row.get(5)?,
+        })
+    }
 }
 
 struct Database {
     conn: Connection,
+    calendar: calendar::Calendar,
 }
 
 impl Database {
-    fn new() -> Result<Self> {
+    fn new(calendar: calendar::Calendar) -> Result<Self> {
         let db_path = get_db_path()?;
 
         // Create directory if it doesn't exist
@@ -314,75 +714,74 @@ impl Database {
             fs::create_dir_all(parent)?;
         }
 
-        let conn = Connection::open(&db_path)
+        Self::open(&db_path, calendar)
+    }
+
+    // The journal at `db_path`, migrated and ready to use
+    fn open(db_path: &Path, calendar: calendar::Calendar) -> Result<Self> {
+        let mut conn = Connection::open(db_path)
             .with_context(|| format!("Failed to open database at {}", db_path.display()))?;
 
-        // Create table if it doesn't exist
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS creative_prompts (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                sync_id TEXT UNIQUE NOT NULL DEFAULT (lower(hex(randomblob(16)))),
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                modified_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                prompt TEXT NOT NULL,
-                response TEXT,
-                drawing_vector TEXT,
-                drawing_preview TEXT,
-                metadata JSON DEFAULT '{}'
-            )",
-            [],
+        migrations::migrate(&mut conn, db_path)?;
+        search::register_functions(&conn)?;
+        calendar::register_functions(&conn, &calendar)?;
+
+        let db = Database { conn, calendar };
+        db.purge_expired_tombstones()?;
+
+        Ok(db)
+    }
+
+    // Store today's prompt before the editor opens, so an aborted session
+    // comes back to the same prompt instead of asking for a new one.
+    fn add_pending_entry(&self, prompt: &providers::Prompt, tags: &[String]) -> Result<i64> {
+        let now = self.calendar.timestamp();
+        let mut metadata = prompt.metadata.clone();
+
+        // The prompt's own tags are the entry's too, alongside any from --tag
+        let mut entry_tags: Vec<String> = metadata
+            .get("prompt_tags")
+            .and_then(|prompt_tags| serde_json::from_value(prompt_tags.clone()).ok())
+            .unwrap_or_default();
+        entry_tags.extend(tags.iter().cloned());
+        metadata.insert("tags".to_string(), serde_json::json!(tags::normalize_tags(&entry_tags)));
+
+        self.conn.execute(
+            "INSERT INTO creative_prompts (prompt, created_at, modified_at, metadata) VALUES (?1, ?2, ?3, ?4)",
+            params![prompt.text, now, now, serde_json::Value::Object(metadata).to_string()],
         )?;
 
-        Ok(Database { conn })
+        Ok(self.conn.last_insert_rowid())
     }
 
-    fn add_entry(&self, prompt: &str, response: &str) -> Result<()> {
-        let now = Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string();
+    fn complete_entry(&self, id: i64, response: &str) -> Result<()> {
+        let now = self.calendar.timestamp();
 
         self.conn.execute(
-            "INSERT INTO creative_prompts (prompt, response, created_at, modified_at) VALUES (?1, ?2, ?3, ?4)",
-            params![prompt, response, now, now],
+            "UPDATE creative_prompts SET response = ?1, modified_at = ?2 WHERE id = ?3",
+            params![response, now, id],
         )?;
 
         println!("✓ Creative disruption processed and stored!");
         Ok(())
     }
 
-    fn list_entries(&self, limit: u32) -> Result<Vec<CreativityEntry>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, prompt, response, created_at FROM creative_prompts
-             ORDER BY created_at DESC LIMIT ?1"
-        )?;
-
-        let entries = stmt.query_map([limit], |row| {
-            Ok(CreativityEntry {
-                id: row.get(0)?,
-                prompt: row.get(1)?,
-                response: row.get(2)?,
-                created_at: row.get(3)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
+    fn list_entries(&self, limit: u32, tags: &[String]) -> Result<Vec<CreativityEntry>> {
+        let tags = tags::normalize_tags(tags);
+        let mut conditions = vec!["p.deleted_at IS NULL", IS_ANSWERED];
+        conditions.extend(tags.iter().map(|_| tags::HAS_TAG));
 
-        Ok(entries)
-    }
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM creative_prompts p
+             WHERE {} ORDER BY datetime(p.created_at) DESC LIMIT ?",
+            ENTRY_COLUMNS,
+            conditions.join(" AND ")
+        ))?;
 
-    fn search_entries(&self, query: &str) -> Result<Vec<CreativityEntry>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, prompt, response, created_at FROM creative_prompts
-             WHERE prompt LIKE ?1 OR response LIKE ?1
-             ORDER BY created_at DESC"
-        )?;
+        let mut params: Vec<rusqlite::types::Value> = tags.into_iter().map(Into::into).collect();
+        params.push(limit.into());
 
-        let search_term = format!("%{}%", query);
-        let entries = stmt.query_map([search_term], |row| {
-            Ok(CreativityEntry {
-                id: row.get(0)?,
-                prompt: row.get(1)?,
-                response: row.get(2)?,
-                created_at: row.get(3)?,
-            })
-        })?
+        let entries = stmt.query_map(rusqlite::params_from_iter(params), CreativityEntry::from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
         Ok(entries)
@@ -390,19 +789,25 @@ impl Database {
 
     fn get_stats(&self) -> Result<(u32, String, String)> {
         let total: u32 = self.conn.query_row(
-            "SELECT COUNT(*) FROM creative_prompts",
+            &format!("SELECT COUNT(*) FROM creative_prompts p WHERE p.deleted_at IS NULL AND {}", IS_ANSWERED),
             [],
             |row| row.get(0)
         )?;
 
         let first_entry: String = self.conn.query_row(
-            "SELECT created_at FROM creative_prompts ORDER BY created_at ASC LIMIT 1",
+            &format!(
+                "SELECT created_at FROM creative_prompts p WHERE p.deleted_at IS NULL AND {} ORDER BY datetime(created_at) ASC LIMIT 1",
+                IS_ANSWERED
+            ),
             [],
             |row| row.get(0)
         ).unwrap_or_else(|_| "No entries yet".to_string());
 
         let last_entry: String = self.conn.query_row(
-            "SELECT created_at FROM creative_prompts ORDER BY created_at DESC LIMIT 1",
+            &format!(
+                "SELECT created_at FROM creative_prompts p WHERE p.deleted_at IS NULL AND {} ORDER BY datetime(created_at) DESC LIMIT 1",
+                IS_ANSWERED
+            ),
             [],
             |row| row.get(0)
         ).unwrap_or_else(|_| "No entries yet".to_string());
@@ -410,46 +815,29 @@ impl Database {
         Ok((total, first_entry, last_entry))
     }
 
-    fn export_all(&self) -> Result<String> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, prompt, response, created_at FROM creative_prompts ORDER BY created_at ASC"
-        )?;
-
-        let entries = stmt.query_map([], |row| {
-            Ok(serde_json::json!({
-                "id": row.get::<_, i64>(0)?,
-                "prompt": row.get::<_, String>(1)?,
-                "response": row.get::<_, String>(2)?,
-                "created_at": row.get::<_, String>(3)?
-            }))
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
-
-        let export = serde_json::json!({
-            "export_date": Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string(),
-            "total_entries": entries.len(),
-            "entries": entries
-        });
-
-        Ok(serde_json::to_string_pretty(&export)?)
+    fn get_entry(&self, id: i64) -> Result<CreativityEntry> {
+        self.conn.query_row(
+            &format!(
+                "SELECT {} FROM creative_prompts p WHERE p.id = ?1 AND p.deleted_at IS NULL",
+                ENTRY_COLUMNS
+            ),
+            [id],
+            CreativityEntry::from_row,
+        )
+        .optional()?
+        .with_context(|| format!("No entry with ID {}", id))
     }
 
-
     fn get_today_entry(&self) -> Result<Option<CreativityEntry>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, prompt, response, created_at FROM creative_prompts
-             WHERE date(substr(created_at, 1, 10)) = date('now')
-             ORDER BY created_at DESC LIMIT 1"
-        )?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM creative_prompts p
+             WHERE journal_day(p.created_at) = ?1 AND p.deleted_at IS NULL
+             ORDER BY datetime(p.created_at) DESC LIMIT 1",
+            ENTRY_COLUMNS
+        ))?;
 
-        let mut rows = stmt.query_map([], |row| {
-            Ok(CreativityEntry {
-                id: row.get(0)?,
-                prompt: row.get(1)?,
-                response: row.get(2)?,
-                created_at: row.get(3)?,
-            })
-        })?;
+        let today = self.calendar.today().format("%Y-%m-%d").to_string();
+        let mut rows = stmt.query_map([today], CreativityEntry::from_row)?;
 
         match rows.next() {
             Some(entry) => Ok(Some(entry?)),
@@ -460,7 +848,7 @@ impl Database {
 
 fn get_db_path() -> Result<PathBuf> {
     let mut path = dirs::data_local_dir()
-        .or_else(|| dirs::home_dir())
+        .or_else(dirs::home_dir)
         .context("Could not find suitable directory for database")?;
 
             path.push("estrange");
@@ -468,76 +856,129 @@ fn get_db_path() -> Result<PathBuf> {
     Ok(path)
 }
 
+fn run_config_command(explicit: Option<&Path>, action: &ConfigAction) -> Result<()> {
+    match action {
+        ConfigAction::Init { force } => {
+            let path = config::init(explicit, *force)?;
+            println!("✓ Wrote {}", path.display());
+            println!("Add your Gemini API key with: estrange config set gemini_api_key YOUR_KEY");
+        }
+        ConfigAction::Get { key } => println!("{}", config::get(explicit, key)?),
+        ConfigAction::Set { key, value } => {
+            let path = config::set(explicit, key, value)?;
+            println!("✓ Set {} in {}", key, path.display());
+        }
+        ConfigAction::Path => config::print_paths(explicit)?,
+    }
+    Ok(())
+}
+
+fn run_secret_command(action: &SecretAction) -> Result<()> {
+    match action {
+        SecretAction::Set { name } => {
+            if std::io::stdin().is_terminal() {
+                eprintln!("Paste the secret for {}, then press Enter:", name);
+            }
+            let mut secret = String::new();
+            std::io::stdin().read_line(&mut secret)?;
+            let secret = secret.trim();
+            if secret.is_empty() {
+                anyhow::bail!("No secret given - nothing stored");
+            }
+
+            let location = secrets::set(name, secret)?;
+            println!("✓ Stored {} in {}", name, location);
+            println!("Point a setting at it with: estrange config set <setting> keyring:{}", name);
+        }
+        SecretAction::Rm { name } => {
+            secrets::remove(name)?;
+            println!("✓ Removed {}", name);
+        }
+    }
+    Ok(())
+}
+
 // Helper function for the core receive-and-respond flow
-async fn receive_and_respond(db: &Database, manual_mode: bool) -> Result<()> {
-    if let Some(today_entry) = db.get_today_entry()? {
+async fn receive_and_respond(
+    db: &Database,
+    config: &Config,
+    manual_mode: bool,
+    generator: Option<&str>,
+    tags: &[String],
+) -> Result<()> {
+    let generator = generators::resolve(config, generator)?;
+    let editor = editor::Editor::from_config(config)?;
+    let today_entry = db.get_today_entry()?;
+
+    if let Some(entry) = today_entry.as_ref().filter(|e| !e.pending) {
         println!("🌅 Today's creative disruption already processed:");
-        print_entry(&today_entry);
+        print_entry(entry);
         println!("💫 Carry this strangeness with you. Tomorrow brings new disruption.");
         return Ok(());
     }
     // Start timing the entire session
     let mut timer = SessionTimer::new();
 
-    let prompt = if manual_mode {
-        println!("📝 Enter your creative prompt:");
-        let mut manual_prompt = String::new();
-        std::io::stdin().read_line(&mut manual_prompt)?;
-        let prompt = manual_prompt.trim().to_string();
-        if let Some(m) = metrics() {
-            m.prompt_manual(prompt.len());
-        }
-        prompt
-    } else {
-        println!("🌀 Receiving today's creative disruption...");
-        let gemini = GeminiClient::new()?;
-        let prompt_template = "Generate one unexpected creative stimulus - it could be anything: an object, a situation, a constraint, a weird fact, a made-up rule, or anything else that could spark ideas. Just give me the one thing, no explanation.";
-
-        match gemini.generate_prompt(prompt_template).await {
-            Ok(generated_prompt) => {
-                if let Some(m) = metrics() {
-                    m.prompt_generated(generated_prompt.len());
-                }
-                println!("✨ {}", generated_prompt);
-                generated_prompt
-            }
-            Err(e) => {
-                eprintln!("⚠️  Disruption generator unavailable: {}", e);
-                println!("📝 Please enter a prompt manually:");
-                let mut manual_prompt = String::new();
-                std::io::stdin().read_line(&mut manual_prompt)?;
-                let prompt = manual_prompt.trim().to_string();
-                if let Some(m) = metrics() {
-                    m.prompt_manual(prompt.len());
-                }
-                prompt
+    let (entry_id, prompt, follow_ups) = match today_entry {
+        Some(entry) => {
+            println!("🌀 Today's creative disruption, received {}:", entry.created_at);
+            print_prompt(&entry.prompt, &entry.follow_ups);
+            if !tags.is_empty() {
+                db.add_tags(entry.id, tags)?;
             }
+            (entry.id, entry.prompt, entry.follow_ups)
+        }
+        None => {
+            let prompt = receive_prompt(db, config, &generator, manual_mode).await?;
+            (db.add_pending_entry(&prompt, tags)?, prompt.text.clone(), prompt.follow_ups())
         }
     };
 
-    if prompt.is_empty() {
-        anyhow::bail!("The void cannot prompt creativity");
+    let mut notes = vec!["Creative Response Entry".to_string(), format!("Disruption: {}", prompt)];
+    notes.extend(follow_ups.iter().map(|q| format!("  {}", q)));
+    notes.push(format!("Date: {}", db.calendar.now().format("%Y-%m-%d %H:%M %:z")));
+    notes.push(String::new());
+    notes.push("Let the strangeness work through you...".to_string());
+    let front_matter = editor.front_matter.map(|format| format.skeleton()).unwrap_or_default();
+    let template = template::compose(&front_matter, &notes);
+
+    // Write into a draft kept beside the journal rather than a temp file, so
+    // nothing is lost if the editor or the save below fails.
+    let draft = drafts::draft_path(&db.entry_sync_id(entry_id)?, &editor.extension)?;
+    if !drafts::offer_resume(&draft)? {
+        fs::write(&draft, template)?;
     }
 
-    let template = format!(
-        "# Creative Response Entry\n# Disruption: {}\n# Date: {}\n#\n# Let the strangeness work through you...\n# Lines starting with # will be ignored\n\n",
-        prompt,
-        Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
-    );
-
     // Start timing the actual response writing
     timer.start_response();
 
-    let response = match get_editor_input(&template) {
-            Ok(resp) => {
+    let written = editor.edit(&draft).and_then(|text| match editor.front_matter {
+        Some(format) => template::split_front_matter(&text, format),
+        None => Ok((template::FrontMatter::default(), text)),
+    });
+    let (front_matter, response) = match written {
+            Ok((front_matter, resp)) => {
                 if resp.is_empty() {
                     timer.abandon();
-                    anyhow::bail!("Silence is also a response, but not today");
+                    // A title, mood or tags alone are worth coming back to
+                    if front_matter.is_empty() {
+                        let _ = fs::remove_file(&draft);
+                    } else {
+                        eprintln!("💾 Your draft is kept at {} - run 'estrange' to pick it up again", draft.display());
+                    }
+                    anyhow::bail!("Silence is also a response, but not today. Your prompt will be waiting when you return");
                 }
-                resp
+                (front_matter, resp)
             }
             Err(e) => {
                 timer.abandon();
+                // Nothing written past the template isn't worth keeping
+                let content = fs::read_to_string(&draft).unwrap_or_default();
+                if template::word_count(&content) == 0 {
+                    let _ = fs::remove_file(&draft);
+                } else {
+                    eprintln!("💾 Your draft is kept at {} - run 'estrange' to pick it up again", draft.display());
+                }
                 return Err(e);
             }
         };
@@ -545,7 +986,11 @@ async fn receive_and_respond(db: &Database, manual_mode: bool) -> Result<()> {
     // Record successful completion with timing
     timer.finish_with_response(&response);
 
-    db.add_entry(&prompt, &response)?;
+    if let Err(e) = db.complete_entry(entry_id, &response).and_then(|_| db.apply_front_matter(entry_id, &front_matter)) {
+        eprintln!("💾 Your draft is kept at {} - run 'estrange' to pick it up again", draft.display());
+        return Err(e);
+    }
+    fs::remove_file(&draft)?;
 
     // Update metrics after adding entry
     if let Err(e) = db.update_metrics() {
@@ -554,40 +999,149 @@ async fn receive_and_respond(db: &Database, manual_mode: bool) -> Result<()> {
     Ok(())
 }
 
-fn get_editor_input(template: &str) -> Result<String> {
-    let editor = env::var("EDITOR").unwrap_or_else(|_| "vim".to_string());
+// Ask for a fresh prompt - typed in, or generated - for a day that has none yet.
+async fn receive_prompt(
+    db: &Database,
+    config: &Config,
+    generator: &generators::Generator,
+    manual_mode: bool,
+) -> Result<providers::Prompt> {
+    let prompt = if manual_mode {
+        println!("📝 Enter your creative prompt:");
+        read_manual_prompt()?
+    } else {
+        // Configuration mistakes are reported, not papered over with manual entry
+        let threshold = similarity::threshold(config.similarity_threshold)?;
+        let chain = providers::ProviderChain::from_config(config, db)?;
+        println!("🌀 Receiving today's creative disruption...");
+        match generate_fresh_prompt(db, &chain, generator, threshold).await {
+            Ok(generated_prompt) => {
+                if let Some(m) = metrics() {
+                    m.prompt_generated(generated_prompt.text.len());
+                }
+                print_prompt(&generated_prompt.text, &generated_prompt.follow_ups());
+                generated_prompt
+            }
+            Err(e) => {
+                eprintln!("⚠️  Disruption generator unavailable: {}", e);
+                println!("📝 Please enter a prompt manually:");
+                read_manual_prompt()?
+            }
+        }
+    };
+
+    if prompt.text.is_empty() {
+        anyhow::bail!("The void cannot prompt creativity");
+    }
 
-    let mut temp_file = NamedTempFile::new()?;
-    writeln!(temp_file, "{}", template)?;
+    Ok(prompt)
+}
 
-    let status = Command::new(&editor)
-        .arg(temp_file.path())
-        .status()
-        .with_context(|| format!("Failed to open editor: {}", editor))?;
+// Generate a prompt unlike any this journal has had. A repeat is asked for
+// again, a bounded number of times, with the repeats and recent prompts
+// listed as ones to avoid. Rejections are kept in the entry's metadata.
+async fn generate_fresh_prompt(
+    db: &Database,
+    chain: &providers::ProviderChain,
+    generator: &generators::Generator,
+    threshold: f64,
+) -> Result<providers::Prompt> {
+    let history = db.past_prompts()?;
+    let mut rejected: Vec<String> = Vec::new();
+    let mut rejections: Vec<serde_json::Value> = Vec::new();
+    let mut least_similar: Option<(f64, providers::Prompt)> = None;
+
+    for attempt in 0..=similarity::MAX_REGENERATIONS {
+        let instruction = if attempt == 0 {
+            generator.template.clone()
+        } else {
+            similarity::with_avoid_list(&generator.template, &history, &rejected)
+        };
+        let request = providers::PromptRequest {
+            instruction,
+            generator: generator.name.clone(),
+            day: db.calendar.today(),
+        };
+        let prompt = chain.generate(&request).await?;
 
-    if !status.success() {
-        anyhow::bail!("Editor exited with non-zero status");
+        // Decks, grammars and the daily prompt give the same answer for the
+        // same day, and decks already avoid repeats
+        if !prompt.is_generated() {
+            return Ok(prompt);
+        }
+        // Provenance: providers record their model and settings; the
+        // template is the generator's, before any avoid list was added
+        let prompt = prompt.with("template", generator.template.clone());
+
+        let (score, past) = match similarity::closest(&prompt.text, &history) {
+            Some((score, past)) if score >= threshold => (score, past),
+            _ if rejections.is_empty() => return Ok(prompt),
+            _ => return Ok(prompt.with("rejected_prompts", rejections)),
+        };
+
+        eprintln!(
+            "♻️  \"{}\" is too close to an earlier prompt ({:.0}% like \"{}\")",
+            prompt.text,
+            score * 100.0,
+            past
+        );
+        rejections.push(serde_json::json!({ "prompt": prompt.text, "similar_to": past, "similarity": score }));
+        if !rejected.contains(&prompt.text) {
+            rejected.push(prompt.text.clone());
+        }
+        if least_similar.as_ref().is_none_or(|(best, _)| score < *best) {
+            least_similar = Some((score, prompt));
+        }
+    }
+
+    let (_, prompt) = least_similar.context("No prompt was generated")?;
+    eprintln!(
+        "♻️  Still no fresh prompt after {} tries - keeping the least familiar one",
+        similarity::MAX_REGENERATIONS + 1
+    );
+    rejections.retain(|rejection| rejection["prompt"] != prompt.text.as_str());
+    Ok(prompt.with("rejected_prompts", rejections))
+}
+
+fn read_manual_prompt() -> Result<providers::Prompt> {
+    let mut manual_prompt = String::new();
+    std::io::stdin().read_line(&mut manual_prompt)?;
+    let prompt = manual_prompt.trim().to_string();
+    if let Some(m) = metrics() {
+        m.prompt_manual(prompt.len());
     }
+    Ok(providers::Prompt::new(prompt, "manual"))
+}
 
-    let content = fs::read_to_string(temp_file.path())?;
+fn get_editor_input(editor: &editor::Editor, template: &str) -> Result<String> {
+    let mut temp_file = tempfile::Builder::new().suffix(&format!(".{}", editor.extension)).tempfile()?;
+    write!(temp_file, "{}", template)?;
 
-    // Remove comment lines and trim
-    let cleaned: String = content
-        .lines()
-        .filter(|line| !line.trim_start().starts_with('#'))
-        .collect::<Vec<_>>()
-        .join("\n")
-        .trim()
-        .to_string();
+    editor.edit(temp_file.path())
+}
 
-    Ok(cleaned)
+fn print_prompt(prompt: &str, follow_ups: &[String]) {
+    println!("✨ {}", prompt);
+    for question in follow_ups {
+        println!("   {}", question);
+    }
 }
 
 fn print_entry(entry: &CreativityEntry) {
     println!("─────────────────────────────────────────────────────────────");
     println!("ID: {} | Date: {}", entry.id, entry.created_at);
     println!("Prompt: {}", entry.prompt);
+    for question in &entry.follow_ups {
+        println!("        {}", question);
+    }
+    if !entry.tags.is_empty() {
+        println!("Tags: {}", tags::format_tags(&entry.tags));
+    }
     println!();
-    println!("{}", entry.response);
+    match &entry.response {
+        Some(response) => println!("{}", response),
+        None if entry.pending => println!("⏳ Awaiting your response - run 'estrange' to write it"),
+        None => println!("🎨 A sketched response (open it in the web app)"),
+    }
     println!();
 }
@@ -9,7 +9,9 @@ use prometheus_reqwest_remote_write::WriteRequest;
 use std::sync::OnceLock;
 use std::time::Instant;
 
-use crate::{user_agent::{build_user_agent}, Config, Database};
+use crate::http::{self, HttpClient};
+use crate::secrets;
+use crate::{Config, Database, IS_ANSWERED};
 
 // Global metrics registry
 static METRICS: OnceLock<EstrangeMetrics> = OnceLock::new();
@@ -203,42 +205,43 @@ pub fn metrics() -> Option<&'static EstrangeMetrics> {
 }
 
 // Async function to send metrics to Grafana Cloud
-pub async fn send_metrics_to_grafana() -> Result<()> {
-    let config = Config::load()?;
-
-    let Some(metrics_config) = config.metrics else {
-        return Ok(());
+// Where and as whom to push metrics, or None when pushing is switched off.
+// Without a push URL there's nowhere to send them, so that counts as off too.
+fn grafana_target(config: &Config) -> Result<Option<(String, String, String)>> {
+    let Some(metrics_config) = config.metrics.clone() else {
+        return Ok(None);
     };
 
-    if let Some(metrics_enabled) = metrics_config.metrics_enabled {
-        if !metrics_enabled {
-            return Ok(());
-        }
+    if metrics_config.metrics_enabled == Some(false) {
+        return Ok(None);
     }
 
-    let grafana_url = metrics_config.grafana_push_url
-        .ok_or_else(|| anyhow::anyhow!("grafana_push_url not configured"))?;
+    let Some(grafana_url) = metrics_config.grafana_push_url else {
+        return Ok(None);
+    };
     let grafana_user = metrics_config.grafana_user
         .ok_or_else(|| anyhow::anyhow!("grafana_user not configured"))?;
     let grafana_token = metrics_config.grafana_token
         .ok_or_else(|| anyhow::anyhow!("grafana_token not configured"))?;
+    let grafana_token = secrets::resolve_setting("metrics.grafana_token", &grafana_token)?;
 
-    let metrics_data = if let Some(m) = metrics() {
-        m.export_metrics()
-    } else {
-        return Ok(());
-    };
+    Ok(Some((grafana_url, grafana_user, grafana_token)))
+}
+
+async fn push(config: &Config, target: &(String, String, String), metrics_data: Vec<MetricFamily>) -> Result<()> {
+    let (grafana_url, grafana_user, grafana_token) = target;
 
     // Compress the metrics data with snappy
     let write_request = WriteRequest::from_metric_families(metrics_data, None).map_err(|e| {
         anyhow::anyhow!("Failed to create WriteRequest: {}", e)
     })?;
 
-    let client = reqwest::Client::new();
-    let http_request = write_request.build_http_request(client.clone(), &grafana_url, &build_user_agent("estrange", "0.1.1"))?;
+    let client = HttpClient::new(config.http.as_ref())?;
+    let http_request = write_request.build_http_request(client.client().clone(), grafana_url, &http::user_agent())?;
 
-    let response = reqwest::RequestBuilder::from_parts(client, http_request).basic_auth(&grafana_user, Some(&grafana_token))
-        .send()
+    let request = reqwest::RequestBuilder::from_parts(client.client().clone(), http_request).basic_auth(grafana_user, Some(grafana_token));
+    let response = client
+        .send(request)
         .await
         .context("Failed to send HTTP request to Grafana")?;
 
@@ -250,10 +253,37 @@ pub async fn send_metrics_to_grafana() -> Result<()> {
         anyhow::bail!("Grafana API error {}: {}", status, error_body);
     }
 
+    Ok(())
+}
+
+pub async fn send_metrics_to_grafana(config: &Config) -> Result<()> {
+    let Some(target) = grafana_target(config)? else {
+        return Ok(());
+    };
+
+    let metrics_data = if let Some(m) = metrics() {
+        m.export_metrics()
+    } else {
+        return Ok(());
+    };
+
+    push(config, &target, metrics_data).await?;
+
     println!("✅ Metrics successfully sent to Grafana");
     Ok(())
 }
 
+// Push an empty batch, so the credentials are checked without recording
+// anything. Returns the push URL, or None when pushing is switched off.
+pub async fn check_grafana(config: &Config) -> Result<Option<String>> {
+    let Some(target) = grafana_target(config)? else {
+        return Ok(None);
+    };
+
+    push(config, &target, Vec::new()).await?;
+    Ok(Some(target.0))
+}
+
 // Timer helper for measuring both response time and total session time
 pub struct SessionTimer {
     session_start: Instant,
@@ -315,14 +345,16 @@ impl Drop for SessionTimer {
 // Add to your Database impl for tracking streaks
 impl Database {
     pub fn calculate_current_streak(&self) -> Result<i64> {
-        let mut stmt = self.conn.prepare(
-            "SELECT date(substr(created_at, 1, 10)) as date_only
-             FROM creative_prompts
-             ORDER BY date_only DESC"
-        )?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT DISTINCT journal_day(created_at) as day
+             FROM creative_prompts p
+             WHERE p.deleted_at IS NULL AND {}
+             ORDER BY day DESC",
+            IS_ANSWERED
+        ))?;
 
         let dates: Vec<String> = stmt.query_map([], |row| {
-            Ok(row.get::<_, String>(0)?)
+            row.get::<_, String>(0)
         })?
         .collect::<Result<Vec<_>, _>>()?;
 
@@ -331,7 +363,7 @@ impl Database {
         }
 
         let mut streak = 0i64;
-        let mut current_date = chrono::Utc::now().date_naive();
+        let mut current_date = self.calendar.today();
 
         for date_str in dates {
             let entry_date = chrono::NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")?;
@@ -350,7 +382,7 @@ impl Database {
 
     pub fn update_metrics(&self) -> Result<()> {
         let total: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM creative_prompts",
+            &format!("SELECT COUNT(*) FROM creative_prompts p WHERE p.deleted_at IS NULL AND {}", IS_ANSWERED),
             [],
             |row| row.get(0)
         )?;
@@ -367,7 +399,7 @@ impl Database {
 }
 
 // Update your Config struct to include Grafana settings
-#[derive(Serialize, Deserialize, Default)]
+#[derive(Serialize, Deserialize, Default, Clone)]
 pub struct MetricsConfig {
     // Add these to your existing Config struct
     grafana_push_url: Option<String>,
@@ -0,0 +1,244 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use rusqlite::Connection;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Ordered schema migrations. Entry N moves a database from `user_version` N
+// to N + 1. Never edit a migration that has shipped - append a new one.
+const MIGRATIONS: &[&str] = &[
+    // 1: the original journal table. `IF NOT EXISTS` lets databases created
+    // before migrations existed (user_version 0) adopt this history as-is.
+    "CREATE TABLE IF NOT EXISTS creative_prompts (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        sync_id TEXT UNIQUE NOT NULL DEFAULT (lower(hex(randomblob(16)))),
+        created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+        modified_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+        prompt TEXT NOT NULL,
+        response TEXT,
+        drawing_vector TEXT,
+        drawing_preview TEXT,
+        metadata JSON DEFAULT '{}'
+    );",
+    // 2: full-text index over prompts and responses for `excavate`, kept in
+    // sync with the journal by triggers.
+    "CREATE VIRTUAL TABLE creative_prompts_fts USING fts5(
+        prompt,
+        response,
+        content = 'creative_prompts',
+        content_rowid = 'id',
+        tokenize = 'porter unicode61'
+    );
+    CREATE TRIGGER creative_prompts_fts_insert AFTER INSERT ON creative_prompts BEGIN
+        INSERT INTO creative_prompts_fts (rowid, prompt, response)
+        VALUES (new.id, new.prompt, new.response);
+    END;
+    CREATE TRIGGER creative_prompts_fts_delete AFTER DELETE ON creative_prompts BEGIN
+        INSERT INTO creative_prompts_fts (creative_prompts_fts, rowid, prompt, response)
+        VALUES ('delete', old.id, old.prompt, old.response);
+    END;
+    CREATE TRIGGER creative_prompts_fts_update AFTER UPDATE OF prompt, response ON creative_prompts BEGIN
+        INSERT INTO creative_prompts_fts (creative_prompts_fts, rowid, prompt, response)
+        VALUES ('delete', old.id, old.prompt, old.response);
+        INSERT INTO creative_prompts_fts (rowid, prompt, response)
+        VALUES (new.id, new.prompt, new.response);
+    END;
+    INSERT INTO creative_prompts_fts (creative_prompts_fts) VALUES ('rebuild');",
+    // 3: earlier versions of revised responses. `modified_at` is when that
+    // text became current, `replaced_at` when a revision superseded it.
+    "CREATE TABLE entry_revisions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        entry_id INTEGER NOT NULL REFERENCES creative_prompts (id) ON DELETE CASCADE,
+        response TEXT,
+        modified_at DATETIME NOT NULL,
+        replaced_at DATETIME NOT NULL
+    );
+    CREATE INDEX entry_revisions_entry_id ON entry_revisions (entry_id);",
+    // 4: soft delete. Forgotten entries keep their row as a tombstone.
+    "ALTER TABLE creative_prompts ADD COLUMN deleted_at DATETIME;",
+    // 5: RFC 3339 timestamps. Older rows were written as
+    // "2025-01-31 18:04:00 UTC" (or CURRENT_TIMESTAMP, also UTC).
+    "UPDATE creative_prompts SET created_at = substr(created_at, 1, 10) || 'T' || substr(created_at, 12, 8) || 'Z'
+        WHERE created_at GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9] [0-9][0-9]:[0-9][0-9]:[0-9][0-9]*';
+    UPDATE creative_prompts SET modified_at = substr(modified_at, 1, 10) || 'T' || substr(modified_at, 12, 8) || 'Z'
+        WHERE modified_at GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9] [0-9][0-9]:[0-9][0-9]:[0-9][0-9]*';
+    UPDATE creative_prompts SET deleted_at = substr(deleted_at, 1, 10) || 'T' || substr(deleted_at, 12, 8) || 'Z'
+        WHERE deleted_at GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9] [0-9][0-9]:[0-9][0-9]:[0-9][0-9]*';
+    UPDATE entry_revisions SET modified_at = substr(modified_at, 1, 10) || 'T' || substr(modified_at, 12, 8) || 'Z'
+        WHERE modified_at GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9] [0-9][0-9]:[0-9][0-9]:[0-9][0-9]*';
+    UPDATE entry_revisions SET replaced_at = substr(replaced_at, 1, 10) || 'T' || substr(replaced_at, 12, 8) || 'Z'
+        WHERE replaced_at GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9] [0-9][0-9]:[0-9][0-9]:[0-9][0-9]*';",
+    // 6: per-journal settings. `seed` makes deterministic choices (like the
+    // day's deck card) differ between journals but stay stable within one.
+    "CREATE TABLE journal_meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    INSERT INTO journal_meta (key, value) VALUES ('seed', lower(hex(randomblob(8))));",
+];
+
+pub fn latest_version() -> i64 {
+    MIGRATIONS.len() as i64
+}
+
+pub fn schema_version(conn: &Connection) -> Result<i64> {
+    let version = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    Ok(version)
+}
+
+// Bring the schema up to date, backing up the database file first if it
+// already holds data. Refuses to touch databases written by a newer estrange.
+pub fn migrate(conn: &mut Connection, db_path: &Path) -> Result<()> {
+    let current = schema_version(conn)?;
+    let latest = latest_version();
+
+    if current > latest {
+        anyhow::bail!(
+            "Database at {} uses schema version {}, but this estrange only understands up to version {}. Upgrade estrange to open it.",
+            db_path.display(),
+            current,
+            latest
+        );
+    }
+
+    if current == latest {
+        return Ok(());
+    }
+
+    let has_tables: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table')",
+        [],
+        |row| row.get(0),
+    )?;
+
+    if has_tables {
+        let backup_path = backup(db_path, current)?;
+        eprintln!(
+            "💾 Upgrading journal schema v{} → v{} (backup saved to {})",
+            current,
+            latest,
+            backup_path.display()
+        );
+    }
+
+    for (index, sql) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+        let version = index as i64 + 1;
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)
+            .with_context(|| format!("Failed to apply schema migration {}", version))?;
+        tx.pragma_update(None, "user_version", version)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+fn backup(db_path: &Path, version: i64) -> Result<PathBuf> {
+    let file_name = db_path
+        .file_name()
+        .context("Database path has no file name")?
+        .to_string_lossy();
+
+    let backup_path = db_path.with_file_name(format!(
+        "{}.v{}-{}.bak",
+        file_name,
+        version,
+        Utc::now().format("%Y%m%d%H%M%S")
+    ));
+
+    fs::copy(db_path, &backup_path)
+        .with_context(|| format!("Failed to back up database to {}", backup_path.display()))?;
+
+    Ok(backup_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A journal as estrange wrote it before migrations existed
+    fn legacy_database(path: &Path) -> Connection {
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE creative_prompts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                sync_id TEXT UNIQUE NOT NULL DEFAULT (lower(hex(randomblob(16)))),
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                modified_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                prompt TEXT NOT NULL,
+                response TEXT,
+                drawing_vector TEXT,
+                drawing_preview TEXT,
+                metadata JSON DEFAULT '{}'
+            );
+            INSERT INTO creative_prompts (created_at, modified_at, prompt, response)
+            VALUES ('2025-01-31 18:04:00 UTC', '2025-01-31 18:30:12 UTC', 'Draw a door', 'A blue door on a Tuesday');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn backups(dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "bak"))
+            .collect()
+    }
+
+    #[test]
+    fn upgrades_a_legacy_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("estrange.db");
+        let mut conn = legacy_database(&path);
+
+        migrate(&mut conn, &path).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), latest_version());
+
+        let (created_at, modified_at): (String, String) = conn
+            .query_row("SELECT created_at, modified_at FROM creative_prompts", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(created_at, "2025-01-31T18:04:00Z");
+        assert_eq!(modified_at, "2025-01-31T18:30:12Z");
+
+        // The index was rebuilt over the rows that were already there
+        let matches: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM creative_prompts_fts WHERE creative_prompts_fts MATCH 'tuesday'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(matches, 1);
+
+        assert_eq!(backups(dir.path()).len(), 1);
+    }
+
+    #[test]
+    fn refuses_a_newer_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("estrange.db");
+        let mut conn = Connection::open(&path).unwrap();
+        conn.pragma_update(None, "user_version", latest_version() + 1).unwrap();
+
+        let error = migrate(&mut conn, &path).unwrap_err();
+        assert!(error.to_string().contains("Upgrade estrange"));
+        assert_eq!(schema_version(&conn).unwrap(), latest_version() + 1);
+    }
+
+    #[test]
+    fn backs_up_only_existing_journals() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("estrange.db");
+        let mut conn = Connection::open(&path).unwrap();
+
+        migrate(&mut conn, &path).unwrap();
+        assert!(backups(dir.path()).is_empty());
+
+        // Already up to date: nothing to back up either
+        migrate(&mut conn, &path).unwrap();
+        assert!(backups(dir.path()).is_empty());
+    }
+}
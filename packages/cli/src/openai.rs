@@ -0,0 +1,153 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::generation::GenerationConfig;
+use crate::http::HttpClient;
+use crate::providers::{Prompt, PromptProvider, PromptRequest};
+use crate::secrets;
+
+// API structures for OpenAI-style chat completions
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ChatMessage {
+    role: String,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+// Talks to anything that speaks /v1/chat/completions - OpenAI itself, or a
+// local Ollama (http://localhost:11434/v1) or llama.cpp server.
+pub struct OpenAiClient {
+    http: HttpClient,
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+    generation: GenerationConfig,
+}
+
+impl OpenAiClient {
+    pub fn new(
+        http: HttpClient,
+        base_url: String,
+        model: String,
+        api_key: Option<String>,
+        generation: GenerationConfig,
+    ) -> Self {
+        OpenAiClient {
+            http,
+            base_url,
+            model,
+            api_key,
+            generation,
+        }
+    }
+
+    // Local servers often need no key at all
+    fn api_key(&self) -> Result<Option<String>> {
+        self.api_key
+            .as_deref()
+            .map(|reference| secrets::resolve_setting(&format!("the API key for {}", self.base_url), reference))
+            .transpose()
+    }
+}
+
+#[async_trait]
+impl PromptProvider for OpenAiClient {
+    fn name(&self) -> String {
+        format!("{} ({})", self.base_url, self.model)
+    }
+
+    async fn generate(&self, request: &PromptRequest) -> Result<Prompt> {
+        let mut messages = Vec::new();
+        if let Some(system_instruction) = &self.generation.system_instruction {
+            messages.push(ChatMessage {
+                role: "system".to_string(),
+                content: system_instruction.clone(),
+            });
+        }
+        messages.push(ChatMessage {
+            role: "user".to_string(),
+            content: request.instruction.clone(),
+        });
+
+        let body = ChatRequest {
+            model: &self.model,
+            messages,
+            temperature: self.generation.temperature,
+            top_p: self.generation.top_p,
+        };
+
+        let url = format!("{}/chat/completions", self.base_url.trim_end_matches('/'));
+        let mut http_request = self.http.post(&url).json(&body);
+        if let Some(api_key) = self.api_key()? {
+            http_request = http_request.bearer_auth(api_key);
+        }
+
+        let response = self
+            .http
+            .send(http_request)
+            .await
+            .with_context(|| format!("Failed to send request to {}", url))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            anyhow::bail!("Chat completions API error: {}", error_text);
+        }
+
+        let chat_response: ChatResponse = response
+            .json()
+            .await
+            .context("Failed to parse chat completions response")?;
+
+        let generated_text = chat_response
+            .choices
+            .first()
+            .map(|c| c.message.content.trim().to_string())
+            .context("No content in chat completions response")?;
+
+        let prompt = Prompt::new(generated_text, "openai-compatible")
+            .with("model", self.model.clone())
+            .with("base_url", self.base_url.clone())
+            .with("generator", request.generator.clone());
+        Ok(self.generation.record(prompt))
+    }
+
+    // /models is served by OpenAI, Ollama and llama.cpp alike
+    async fn check(&self) -> Result<String> {
+        let url = format!("{}/models", self.base_url.trim_end_matches('/'));
+        let mut http_request = self.http.get(&url);
+        if let Some(api_key) = self.api_key()? {
+            http_request = http_request.bearer_auth(api_key);
+        }
+
+        let response = self
+            .http
+            .send(http_request)
+            .await
+            .with_context(|| format!("Failed to reach {}", url))?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("{} returned {}", url, response.status());
+        }
+        Ok(format!("{} is answering", self.base_url))
+    }
+}
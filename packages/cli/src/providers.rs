@@ -0,0 +1,221 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::env;
+use std::path::PathBuf;
+
+use crate::daily::DailyPromptClient;
+use crate::deck::Deck;
+use crate::gemini::GeminiClient;
+use crate::generation::GenerationConfig;
+use crate::http::HttpClient;
+use crate::grammar::GrammarProvider;
+use crate::openai::OpenAiClient;
+use crate::{Config, Database};
+
+// What a provider is asked for.
+pub struct PromptRequest {
+    // The instruction for model-backed providers, from the chosen generator
+    pub instruction: String,
+    // Name of that generator, recorded by the providers that use it
+    pub generator: String,
+    // The journal day the prompt is for
+    pub day: NaiveDate,
+}
+
+// A prompt and where it came from. `metadata` is merged into the entry's
+// metadata column, so provenance travels with the entry.
+pub struct Prompt {
+    pub text: String,
+    pub metadata: Map<String, Value>,
+}
+
+impl Prompt {
+    pub fn new(text: impl Into<String>, provider: &str) -> Self {
+        let mut metadata = Map::new();
+        metadata.insert("provider".to_string(), Value::String(provider.to_string()));
+        Prompt { text: text.into(), metadata }
+    }
+
+    pub fn with(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.metadata.insert(key.to_string(), value.into());
+        self
+    }
+
+    // Whether a model wrote the prompt from the request's instruction, so
+    // asking again can give something different
+    pub fn is_generated(&self) -> bool {
+        matches!(
+            self.metadata.get("provider").and_then(Value::as_str),
+            Some("gemini" | "openai-compatible")
+        )
+    }
+
+    // Questions offered alongside the prompt, if the provider gave any
+    pub fn follow_ups(&self) -> Vec<String> {
+        follow_ups_from(self.metadata.get("follow_ups"))
+    }
+}
+
+pub fn follow_ups_from_metadata(metadata: Option<&str>) -> Vec<String> {
+    let metadata = metadata.and_then(|m| serde_json::from_str::<Value>(m).ok());
+    follow_ups_from(metadata.as_ref().and_then(|m| m.get("follow_ups")))
+}
+
+fn follow_ups_from(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(|follow_ups| serde_json::from_value(follow_ups.clone()).ok())
+        .unwrap_or_default()
+}
+
+#[async_trait]
+pub trait PromptProvider: Send + Sync {
+    // Shown when the provider fails and the chain moves on
+    fn name(&self) -> String;
+    async fn generate(&self, request: &PromptRequest) -> Result<Prompt>;
+    // Whether the provider could answer right now, for `estrange doctor`.
+    // Shouldn't spend a generation. Describes what was found.
+    async fn check(&self) -> Result<String>;
+}
+
+// One `[[providers]]` entry in config.toml. The list is tried in order
+// until one returns a prompt.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum ProviderConfig {
+    Gemini {
+        api_key: Option<String>,
+        // Defaults to the top-level `gemini_model`, then gemini-2.5-flash
+        model: Option<String>,
+        base_url: Option<String>,
+        #[serde(flatten)]
+        generation: GenerationConfig,
+    },
+    // Any /v1/chat/completions endpoint: OpenAI, Ollama, llama.cpp, ...
+    OpenaiCompatible {
+        base_url: String,
+        model: String,
+        api_key: Option<String>,
+        #[serde(flatten)]
+        generation: GenerationConfig,
+    },
+    // A prompt deck file (TOML, JSON, or one prompt per line). Without a
+    // path, draws from the bundled deck plus every deck in the data directory.
+    Deck {
+        path: Option<PathBuf>,
+    },
+    // Tracery-style grammar expansion, from a file or the bundled grammar
+    Grammar {
+        path: Option<PathBuf>,
+    },
+    // The shared prompt of the day published by the prompt generator
+    // worker. Defaults to the top-level `daily_prompt_url`, then the
+    // published worker.
+    Daily {
+        url: Option<String>,
+    },
+}
+
+
+// A provider's own sampling settings over the top-level `[generation]` ones
+fn generation_settings(own: &GenerationConfig, config: &Config) -> Result<GenerationConfig> {
+    let settings = own.or(config.generation.as_ref());
+    settings.validate()?;
+    Ok(settings)
+}
+
+pub struct ProviderChain {
+    providers: Vec<Box<dyn PromptProvider>>,
+}
+
+impl ProviderChain {
+    // Without a `[[providers]]` list the chain is Gemini when an API key is
+    // configured, otherwise the shared daily prompt. The prompt decks always
+    // come last, so there is a prompt even offline.
+    pub fn from_config(config: &Config, db: &Database) -> Result<Self> {
+        let mut entries = match &config.providers {
+            Some(providers) => providers.clone(),
+            None => {
+                let has_gemini_key = config.gemini_api_key.is_some()
+                    || env::var("GEMINI_API_KEY").is_ok_and(|key| !key.is_empty());
+                if has_gemini_key {
+                    vec![ProviderConfig::Gemini {
+                        api_key: None,
+                        model: None,
+                        base_url: None,
+                        generation: GenerationConfig::default(),
+                    }]
+                } else {
+                    vec![ProviderConfig::Daily { url: None }]
+                }
+            }
+        };
+        if !entries.iter().any(|entry| matches!(entry, ProviderConfig::Deck { path: None })) {
+            entries.push(ProviderConfig::Deck { path: None });
+        }
+
+        if let Some(generation) = &config.generation {
+            generation.validate()?;
+        }
+        let http = HttpClient::new(config.http.as_ref())?;
+        let providers = entries
+            .into_iter()
+            .map(|entry| -> Result<Box<dyn PromptProvider>> {
+                Ok(match entry {
+                    ProviderConfig::Gemini { api_key, model, base_url, generation } => Box::new(GeminiClient::new(
+                        http.clone(),
+                        api_key.or_else(|| config.gemini_api_key.clone()),
+                        model.or_else(|| config.gemini_model.clone()),
+                        base_url,
+                        generation_settings(&generation, config)?,
+                    )),
+                    ProviderConfig::OpenaiCompatible { base_url, model, api_key, generation } => Box::new(
+                        OpenAiClient::new(http.clone(), base_url, model, api_key, generation_settings(&generation, config)?),
+                    ),
+                    ProviderConfig::Deck { path } => Box::new(Deck::new(path, db)?),
+                    ProviderConfig::Grammar { path } => Box::new(GrammarProvider::new(path, db)?),
+                    ProviderConfig::Daily { url } => {
+                        Box::new(DailyPromptClient::new(http.clone(), url.or_else(|| config.daily_prompt_url.clone())))
+                    }
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(ProviderChain { providers })
+    }
+
+    // Ask each provider in turn, reporting failures as the chain falls through.
+    pub async fn generate(&self, request: &PromptRequest) -> Result<Prompt> {
+        let mut failures = Vec::new();
+
+        for provider in &self.providers {
+            match provider.generate(request).await {
+                Ok(prompt) if !prompt.text.trim().is_empty() => return Ok(prompt),
+                Ok(_) => failures.push(format!("{}: returned an empty prompt", provider.name())),
+                Err(e) => {
+                    if self.providers.len() > 1 {
+                        eprintln!("⚠️  {} unavailable: {}", provider.name(), e);
+                    }
+                    failures.push(format!("{}: {}", provider.name(), e));
+                }
+            }
+        }
+
+        match failures.as_slice() {
+            [] => anyhow::bail!("No prompt providers configured"),
+            [only] => anyhow::bail!("{}", only),
+            _ => anyhow::bail!("every prompt provider failed"),
+        }
+    }
+
+    // Every provider's name and the outcome of its check, in chain order
+    pub async fn check(&self) -> Vec<(String, Result<String>)> {
+        let mut results = Vec::new();
+        for provider in &self.providers {
+            results.push((provider.name(), provider.check().await));
+        }
+        results
+    }
+}
@@ -0,0 +1,399 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use rusqlite::types::Value;
+
+use crate::tags;
+
+// The `excavate` query language. Terms are separated by whitespace and all
+// must match; `OR` between two text terms makes either one enough. OR only
+// joins its neighbours: `tuesday OR door minor` is (tuesday OR door) AND minor.
+//
+//   gravity "sideways on tuesdays" door*   free text (phrases, prefixes)
+//   prompt:doorknob  response:"it hums"    text scoped to one field
+//   tag:physics                            entries carrying a tag
+//   after:2026-01-01  before:2026-03       dates (after is inclusive)
+//   words>300  words<=50                   response length
+//   -tag:physics  NOT response:gravity     negation
+enum Filter {
+    Text { field: Option<Field>, text: String, prefix: bool },
+    Tag(String),
+    Before(NaiveDate),
+    After(NaiveDate),
+    Words(&'static str, i64),
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    Prompt,
+    Response,
+}
+
+impl Field {
+    fn column(self) -> &'static str {
+        match self {
+            Field::Prompt => "prompt",
+            Field::Response => "response",
+        }
+    }
+}
+
+enum Token {
+    Term { negated: bool, filter: Filter },
+    Or,
+}
+
+// A parsed query, ready to splice into SQL over `creative_prompts p`.
+// `fts` is bound to a MATCH against `creative_prompts_fts`; every condition
+// is a parameterized SQL fragment with its values in `params`, in order.
+pub struct CompiledQuery {
+    pub fts: Option<String>,
+    pub conditions: Vec<String>,
+    pub params: Vec<Value>,
+}
+
+pub fn compile(query: &str) -> Result<CompiledQuery> {
+    let tokens = parse(query)?;
+
+    // Runs of text terms joined by OR, each of which must match
+    let mut fts_groups: Vec<Vec<String>> = Vec::new();
+    let mut compiled = CompiledQuery {
+        fts: None,
+        conditions: Vec::new(),
+        params: Vec::new(),
+    };
+
+    for (index, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Or => {
+                let joins_text = |token: Option<&Token>| {
+                    matches!(token, Some(Token::Term { negated: false, filter: Filter::Text { .. } }))
+                };
+                if index == 0 || !joins_text(tokens.get(index - 1)) || !joins_text(tokens.get(index + 1)) {
+                    anyhow::bail!("OR can only join two text terms, like: gravity OR tuesday");
+                }
+            }
+            Token::Term { negated: false, filter: Filter::Text { field, text, prefix } } => {
+                let term = fts_term(*field, text, *prefix);
+                match fts_groups.last_mut() {
+                    Some(group) if index > 0 && matches!(tokens[index - 1], Token::Or) => group.push(term),
+                    _ => fts_groups.push(vec![term]),
+                }
+            }
+            Token::Term { negated: true, filter: Filter::Text { field, text, prefix } } => {
+                compiled.conditions.push(
+                    "p.id NOT IN (SELECT rowid FROM creative_prompts_fts WHERE creative_prompts_fts MATCH ?)".to_string(),
+                );
+                compiled.params.push(Value::Text(fts_term(*field, text, *prefix)));
+            }
+            Token::Term { negated, filter } => {
+                let (condition, value) = match filter {
+                    Filter::Tag(tag) => (tags::HAS_TAG.to_string(), Value::Text(tag.clone())),
+                    Filter::Before(date) => (
+                        "journal_day(p.created_at) < ?".to_string(),
+                        Value::Text(date.format("%Y-%m-%d").to_string()),
+                    ),
+                    Filter::After(date) => (
+                        "journal_day(p.created_at) >= ?".to_string(),
+                        Value::Text(date.format("%Y-%m-%d").to_string()),
+                    ),
+                    Filter::Words(op, count) => {
+                        (format!("word_count(p.response) {} ?", op), Value::Integer(*count))
+                    }
+                    Filter::Text { .. } => unreachable!("text terms are handled above"),
+                };
+
+                compiled.conditions.push(if *negated {
+                    format!("NOT ({})", condition)
+                } else {
+                    condition
+                });
+                compiled.params.push(value);
+            }
+        }
+    }
+
+    // FTS5 binds implicit AND tighter than OR, so alternatives are grouped
+    if !fts_groups.is_empty() {
+        let groups: Vec<String> = fts_groups
+            .into_iter()
+            .map(|group| match group.len() {
+                1 => group.join(""),
+                _ => format!("({})", group.join(" OR ")),
+            })
+            .collect();
+        compiled.fts = Some(groups.join(" AND "));
+    }
+
+    Ok(compiled)
+}
+
+impl CompiledQuery {
+    pub fn require_tag(&mut self, tag: &str) {
+        if let Some(tag) = tags::normalize_tag(tag) {
+            self.conditions.push(tags::HAS_TAG.to_string());
+            self.params.push(Value::Text(tag));
+        }
+    }
+}
+
+// Quote every term so user input can never be read as FTS5 syntax.
+fn fts_term(field: Option<Field>, text: &str, prefix: bool) -> String {
+    let mut term = format!("\"{}\"", text.replace('"', "\"\""));
+    if prefix {
+        term.push('*');
+    }
+    match field {
+        Some(field) => format!("{} : {}", field.column(), term),
+        None => term,
+    }
+}
+
+fn parse(query: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut negate_next = false;
+
+    for word in split_words(query)? {
+        match word.as_str() {
+            "AND" => continue,
+            "OR" => {
+                tokens.push(Token::Or);
+                continue;
+            }
+            "NOT" => {
+                negate_next = true;
+                continue;
+            }
+            _ => {}
+        }
+
+        let (negated, word) = match word.strip_prefix('-') {
+            Some(rest) if !rest.is_empty() => (true, rest),
+            _ => (false, word.as_str()),
+        };
+
+        tokens.push(Token::Term {
+            negated: negated || negate_next,
+            filter: parse_filter(word)?,
+        });
+        negate_next = false;
+    }
+
+    if negate_next {
+        anyhow::bail!("NOT needs something to negate, like: NOT tag:physics");
+    }
+
+    if tokens.is_empty() {
+        anyhow::bail!("Nothing to excavate - give at least one search term");
+    }
+
+    Ok(tokens)
+}
+
+fn parse_filter(word: &str) -> Result<Filter> {
+    let comparison = word.strip_prefix("words").and_then(|rest| {
+        ["<=", ">=", "<", ">", "="]
+            .iter()
+            .find_map(|op| rest.strip_prefix(op).map(|number| (*op, number)))
+    });
+
+    if let Some((op, number)) = comparison {
+        let count = number
+            .parse()
+            .with_context(|| format!("'{}' is not a word count", number))?;
+        return Ok(Filter::Words(op, count));
+    }
+
+    if let Some((key, value)) = word.split_once(':') {
+        let filter = match key {
+            "prompt" => Some(text_filter(Some(Field::Prompt), value)),
+            "response" => Some(text_filter(Some(Field::Response), value)),
+            "tag" => Some(Filter::Tag(tags::normalize_tag(unquote(value)).unwrap_or_default())),
+            "before" => Some(Filter::Before(parse_date(value)?)),
+            "after" => Some(Filter::After(parse_date(value)?)),
+            _ => None,
+        };
+
+        if let Some(filter) = filter {
+            if value.is_empty() || matches!(&filter, Filter::Tag(tag) if tag.is_empty()) {
+                anyhow::bail!("'{}:' needs a value", key);
+            }
+            return Ok(filter);
+        }
+    }
+
+    Ok(text_filter(None, word))
+}
+
+fn text_filter(field: Option<Field>, value: &str) -> Filter {
+    let (value, prefix) = match value.strip_suffix('*') {
+        Some(stem) if !stem.is_empty() => (stem, true),
+        _ => (value, false),
+    };
+
+    Filter::Text {
+        field,
+        text: unquote(value).to_string(),
+        prefix,
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+// Accepts YYYY, YYYY-MM or YYYY-MM-DD; partial dates mean the first day.
+fn parse_date(value: &str) -> Result<NaiveDate> {
+    let padded = match value.len() {
+        4 => format!("{}-01-01", value),
+        7 => format!("{}-01", value),
+        _ => value.to_string(),
+    };
+    NaiveDate::parse_from_str(&padded, "%Y-%m-%d")
+        .with_context(|| format!("'{}' is not a date - use YYYY, YYYY-MM or YYYY-MM-DD", value))
+}
+
+// Split on whitespace, keeping "quoted phrases" (and field:"phrases") whole.
+fn split_words(query: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in query.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+
+    if in_quotes {
+        anyhow::bail!("Unclosed quote in '{}'", query);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &Value) -> &str {
+        match value {
+            Value::Text(text) => text,
+            other => panic!("expected text, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn quotes_free_text_phrases_and_prefixes() {
+        let compiled = compile(r#"gravity "sideways on tuesdays" door*"#).unwrap();
+        assert_eq!(
+            compiled.fts.as_deref(),
+            Some(r#""gravity" AND "sideways on tuesdays" AND "door"*"#)
+        );
+        assert!(compiled.conditions.is_empty());
+        assert!(compiled.params.is_empty());
+    }
+
+    #[test]
+    fn scopes_text_to_a_field() {
+        let compiled = compile(r#"prompt:doorknob response:"it hums""#).unwrap();
+        assert_eq!(compiled.fts.as_deref(), Some(r#"prompt : "doorknob" AND response : "it hums""#));
+    }
+
+    #[test]
+    fn escapes_quotes_inside_terms() {
+        let compiled = compile(r#"say"hi""#).unwrap();
+        assert_eq!(compiled.fts.as_deref(), Some(r#""say""hi""""#));
+    }
+
+    #[test]
+    fn or_joins_only_its_neighbours() {
+        let compiled = compile("tuesday OR door minor").unwrap();
+        assert_eq!(compiled.fts.as_deref(), Some(r#"("tuesday" OR "door") AND "minor""#));
+
+        let compiled = compile("a OR b OR c d OR e").unwrap();
+        assert_eq!(compiled.fts.as_deref(), Some(r#"("a" OR "b" OR "c") AND ("d" OR "e")"#));
+    }
+
+    #[test]
+    fn or_needs_text_on_both_sides() {
+        for query in ["OR gravity", "gravity OR", "gravity OR tag:physics", "gravity OR -tuesday"] {
+            assert!(compile(query).is_err(), "{} should be refused", query);
+        }
+    }
+
+    #[test]
+    fn filters_by_tag() {
+        let compiled = compile("tag:Deep-Time").unwrap();
+        assert_eq!(compiled.fts, None);
+        assert_eq!(compiled.conditions, vec![tags::HAS_TAG.to_string()]);
+        assert_eq!(text(&compiled.params[0]), "deep-time");
+    }
+
+    #[test]
+    fn filters_by_date() {
+        let compiled = compile("after:2026 before:2026-03").unwrap();
+        assert_eq!(
+            compiled.conditions,
+            vec!["journal_day(p.created_at) >= ?", "journal_day(p.created_at) < ?"]
+        );
+        assert_eq!(text(&compiled.params[0]), "2026-01-01");
+        assert_eq!(text(&compiled.params[1]), "2026-03-01");
+
+        assert!(compile("after:someday").is_err());
+    }
+
+    #[test]
+    fn filters_by_word_count() {
+        let compiled = compile("words>300 words<=50").unwrap();
+        assert_eq!(
+            compiled.conditions,
+            vec!["word_count(p.response) > ?", "word_count(p.response) <= ?"]
+        );
+        assert!(matches!(compiled.params[..], [Value::Integer(300), Value::Integer(50)]));
+
+        assert!(compile("words>many").is_err());
+    }
+
+    #[test]
+    fn negates_filters_and_text() {
+        let compiled = compile("-tag:physics NOT response:gravity").unwrap();
+        assert_eq!(compiled.fts, None);
+        assert_eq!(compiled.conditions[0], format!("NOT ({})", tags::HAS_TAG));
+        assert_eq!(
+            compiled.conditions[1],
+            "p.id NOT IN (SELECT rowid FROM creative_prompts_fts WHERE creative_prompts_fts MATCH ?)"
+        );
+        assert_eq!(text(&compiled.params[0]), "physics");
+        assert_eq!(text(&compiled.params[1]), r#"response : "gravity""#);
+    }
+
+    #[test]
+    fn refuses_empty_and_malformed_queries() {
+        for query in ["", "   ", "NOT", "tag:", r#""unclosed"#] {
+            assert!(compile(query).is_err(), "{:?} should be refused", query);
+        }
+    }
+
+    #[test]
+    fn require_tag_adds_a_condition() {
+        let mut compiled = compile("gravity").unwrap();
+        compiled.require_tag("Physics");
+        assert_eq!(compiled.conditions, vec![tags::HAS_TAG.to_string()]);
+        assert_eq!(text(&compiled.params[0]), "physics");
+    }
+}
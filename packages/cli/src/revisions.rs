@@ -0,0 +1,153 @@
+use anyhow::Result;
+use rusqlite::params;
+use similar::{ChangeTag, TextDiff};
+use std::io::IsTerminal;
+
+use crate::editor::Editor;
+use crate::template;
+use crate::{get_editor_input, Database};
+
+pub struct Revision {
+    pub response: String,
+    pub modified_at: String,
+}
+
+impl Database {
+    // Replace an entry's response, keeping the previous text as a revision.
+    pub fn revise_entry(&self, id: i64, response: &str) -> Result<()> {
+        let now = self.calendar.timestamp();
+        let tx = self.conn.unchecked_transaction()?;
+
+        tx.execute(
+            "INSERT INTO entry_revisions (entry_id, response, modified_at, replaced_at)
+             SELECT id, response, modified_at, ?2 FROM creative_prompts WHERE id = ?1",
+            params![id, now],
+        )?;
+        tx.execute(
+            "UPDATE creative_prompts SET response = ?1, modified_at = ?2 WHERE id = ?3",
+            params![response, now, id],
+        )?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    // Every version of an entry's response, oldest first, ending with the
+    // current text.
+    pub fn entry_history(&self, id: i64) -> Result<Vec<Revision>> {
+        let current = self.get_entry(id)?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT response, modified_at FROM entry_revisions WHERE entry_id = ?1 ORDER BY id ASC"
+        )?;
+
+        let mut revisions = stmt.query_map([id], |row| {
+            Ok(Revision {
+                response: row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+                modified_at: row.get(1)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        let modified_at: String = self.conn.query_row(
+            "SELECT modified_at FROM creative_prompts WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )?;
+        revisions.push(Revision {
+            response: current.response.unwrap_or_default(),
+            modified_at,
+        });
+
+        Ok(revisions)
+    }
+}
+
+pub fn revise(db: &Database, editor: &Editor, id: i64) -> Result<()> {
+    let entry = db.get_entry(id)?;
+    if entry.pending {
+        anyhow::bail!("Entry {} is still awaiting its first response - run 'estrange' to write it", id);
+    }
+    let previous = entry.response.unwrap_or_default();
+
+    let notes = [
+        format!("Revising entry {}", entry.id),
+        format!("Disruption: {}", entry.prompt),
+        format!("Written: {}", entry.created_at),
+        String::new(),
+        "Your previous response is above the line.".to_string(),
+    ];
+    let template = template::compose(&previous, &notes);
+
+    let response = get_editor_input(editor, &template)?;
+
+    if response.is_empty() {
+        anyhow::bail!("An empty revision would erase this entry - keeping the original");
+    }
+
+    if response == previous.trim() {
+        println!("🌫️  No changes - entry {} stays as it was", id);
+        return Ok(());
+    }
+
+    db.revise_entry(id, &response)?;
+    println!("✓ Entry {} revised. See how it changed with 'estrange history {}'", id, id);
+    Ok(())
+}
+
+pub fn print_history(db: &Database, id: i64) -> Result<()> {
+    let entry = db.get_entry(id)?;
+    let revisions = db.entry_history(id)?;
+    let color = std::io::stdout().is_terminal();
+
+    println!("📜 History of entry {}: {}", entry.id, entry.prompt);
+
+    for (index, revision) in revisions.iter().enumerate() {
+        let label = match index {
+            0 => " (original)",
+            i if i == revisions.len() - 1 => " (current)",
+            _ => "",
+        };
+        println!("─────────────────────────────────────────────────────────────");
+        println!("Revision {} · {}{}", index + 1, revision.modified_at, label);
+        println!();
+
+        match index.checked_sub(1).map(|previous| &revisions[previous]) {
+            None => println!("{}", revision.response),
+            Some(previous) => print_diff(&previous.response, &revision.response, color),
+        }
+        println!();
+    }
+
+    if revisions.len() == 1 {
+        println!("🌱 Never revised. Revisit it with 'estrange revise {}'", id);
+    }
+
+    Ok(())
+}
+
+fn print_diff(old: &str, new: &str, color: bool) {
+    let diff = TextDiff::from_lines(old, new);
+
+    for (index, group) in diff.grouped_ops(2).iter().enumerate() {
+        if index > 0 {
+            println!("  ┈┈┈");
+        }
+        for op in group {
+            for change in diff.iter_changes(op) {
+                let (sign, style) = match change.tag() {
+                    ChangeTag::Delete => ("-", "\x1b[31m"),
+                    ChangeTag::Insert => ("+", "\x1b[32m"),
+                    ChangeTag::Equal => (" ", ""),
+                };
+                let line = change.to_string_lossy();
+                let line = line.trim_end_matches('\n');
+                if color && !style.is_empty() {
+                    println!("{}{} {}\x1b[0m", style, sign, line);
+                } else {
+                    println!("{} {}", sign, line);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+// Deterministic randomness for choices that must be reproducible: the same
+// journal seed and day always give the same deck card or grammar expansion,
+// on every platform and build (std's hasher makes no such promise).
+
+// FNV-1a with a murmur3 finalizer, so neighbouring inputs (consecutive
+// dates, say) still land far apart.
+pub fn stable_hash(text: &str) -> u64 {
+    let mut hash = text
+        .bytes()
+        .fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ceb9fe1a85ec53);
+    hash ^ (hash >> 33)
+}
+
+// SplitMix64: tiny, fast, and fully determined by its seed.
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // A number in 0..bound. The modulo bias is negligible for rule sizes.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
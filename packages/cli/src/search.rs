@@ -0,0 +1,117 @@
+use anyhow::Result;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+use std::io::IsTerminal;
+
+use crate::query::CompiledQuery;
+use crate::{Database, IS_ANSWERED};
+
+pub struct SearchHit {
+    pub id: i64,
+    pub created_at: String,
+    pub prompt: String,
+    pub snippet: String,
+    // Only text searches are ranked; filter-only queries list newest first.
+    pub score: Option<f64>,
+}
+
+// SQL helpers the query language compiles down to.
+pub fn register_functions(conn: &Connection) -> Result<()> {
+    conn.create_scalar_function(
+        "word_count",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let text = ctx.get::<Option<String>>(0)?;
+            Ok(text.map_or(0, |t| t.split_whitespace().count() as i64))
+        },
+    )?;
+    Ok(())
+}
+
+impl Database {
+    pub fn search_entries(&self, query: &CompiledQuery) -> Result<Vec<SearchHit>> {
+        let mut conditions = vec!["p.deleted_at IS NULL".to_string(), IS_ANSWERED.to_string()];
+        conditions.extend(query.conditions.iter().cloned());
+        let mut params: Vec<Value> = Vec::new();
+
+        let sql = match &query.fts {
+            Some(fts) => {
+                let (open, close) = highlight_markers();
+                params.extend([open, close, open, close].map(|m| Value::Text(m.to_string())));
+                params.push(Value::Text(fts.clone()));
+                conditions.insert(0, "creative_prompts_fts MATCH ?".to_string());
+
+                format!(
+                    "SELECT p.id, p.created_at,
+                            highlight(creative_prompts_fts, 0, ?, ?),
+                            snippet(creative_prompts_fts, 1, ?, ?, '…', 24),
+                            bm25(creative_prompts_fts)
+                     FROM creative_prompts_fts
+                     JOIN creative_prompts p ON p.id = creative_prompts_fts.rowid
+                     WHERE {}
+                     ORDER BY bm25(creative_prompts_fts)",
+                    conditions.join(" AND ")
+                )
+            }
+            None => format!(
+                "SELECT p.id, p.created_at, p.prompt, p.response, NULL
+                 FROM creative_prompts p
+                 WHERE {}
+                 ORDER BY datetime(p.created_at) DESC",
+                conditions.join(" AND ")
+            ),
+        };
+        params.extend(query.params.iter().cloned());
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let hits = stmt.query_map(params_from_iter(params), |row| {
+            let snippet = row.get::<_, Option<String>>(3)?.unwrap_or_default();
+            let score = row.get::<_, Option<f64>>(4)?;
+            Ok(SearchHit {
+                id: row.get(0)?,
+                created_at: row.get(1)?,
+                prompt: row.get(2)?,
+                // Unranked rows carry the whole response; trim it to match
+                // the length of an FTS snippet.
+                snippet: if score.is_some() { snippet } else { truncate_words(&snippet, 24) },
+                // bm25() is negative, lower meaning more relevant
+                score: score.map(|s| -s),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(hits)
+    }
+}
+
+fn truncate_words(text: &str, limit: usize) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.len() <= limit {
+        words.join(" ")
+    } else {
+        format!("{}…", words[..limit].join(" "))
+    }
+}
+
+// Bold yellow on a terminal, Markdown-style emphasis when piped.
+fn highlight_markers() -> (&'static str, &'static str) {
+    if std::io::stdout().is_terminal() {
+        ("\x1b[1;33m", "\x1b[0m")
+    } else {
+        ("**", "**")
+    }
+}
+
+pub fn print_hit(hit: &SearchHit) {
+    println!("─────────────────────────────────────────────────────────────");
+    match hit.score {
+        Some(score) => println!("ID: {} | Date: {} | Relevance: {:.2}", hit.id, hit.created_at, score),
+        None => println!("ID: {} | Date: {}", hit.id, hit.created_at),
+    }
+    println!("Prompt: {}", hit.prompt);
+    println!();
+    println!("{}", hit.snippet);
+    println!();
+}
@@ -0,0 +1,184 @@
+use anyhow::{Context, Result};
+use keyring::Entry;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::get_db_path;
+
+const DEFAULT_SERVICE: &str = "estrange";
+// "file" keeps secrets in the fallback file even when a keyring is available
+pub const STORE_VAR: &str = "ESTRANGE_SECRET_STORE";
+
+// Config values may name a secret rather than hold it:
+//   keyring:estrange/gemini   an entry stored with `estrange secret set`
+//   env:MY_GEMINI_KEY         an environment variable
+//   cmd:pass show gemini      the output of a shell command
+// Anything else is the secret itself.
+pub fn resolve(value: &str) -> Result<String> {
+    let secret = if let Some(name) = value.strip_prefix("keyring:") {
+        get(name)?
+    } else if let Some(var) = value.strip_prefix("env:") {
+        env::var(var).ok().with_context(|| format!("Environment variable {} is not set", var))?
+    } else if let Some(command) = value.strip_prefix("cmd:") {
+        run(command)?
+    } else {
+        return Ok(value.to_string());
+    };
+
+    if secret.is_empty() {
+        anyhow::bail!("{} is empty", value);
+    }
+    Ok(secret)
+}
+
+// Resolve a secret setting when it's about to be used, naming the setting
+// if it fails. Resolving on use keeps a broken `cmd:` (or a locked keyring)
+// from getting in the way of commands that never need the secret.
+pub fn resolve_setting(key: &str, value: &str) -> Result<String> {
+    resolve(value).with_context(|| format!("Couldn't resolve {} ({})", key, value))
+}
+
+fn run(command: &str) -> Result<String> {
+    let output = if cfg!(windows) {
+        Command::new("cmd").args(["/C", command]).output()
+    } else {
+        Command::new("sh").args(["-c", command]).output()
+    }
+    .with_context(|| format!("Failed to run `{}`", command))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        match stderr.trim() {
+            "" => anyhow::bail!("`{}` failed ({})", command, output.status),
+            stderr => anyhow::bail!("`{}` failed ({}): {}", command, output.status, stderr),
+        }
+    }
+    // Only the trailing newline is dropped; `pass` and friends add one
+    Ok(String::from_utf8(output.stdout)
+        .context("Command output isn't UTF-8")?
+        .trim_end_matches(['\r', '\n'])
+        .to_string())
+}
+
+// "gemini" is short for "estrange/gemini"
+fn split_name(name: &str) -> Result<(&str, &str)> {
+    let (service, account) = name.split_once('/').unwrap_or((DEFAULT_SERVICE, name));
+    if service.is_empty() || account.is_empty() {
+        anyhow::bail!("'{}' isn't a secret name - use e.g. gemini or estrange/gemini", name);
+    }
+    Ok((service, account))
+}
+
+// Where secrets live when there's no keyring: a file only you can read,
+// next to the journal.
+fn fallback_path() -> Result<PathBuf> {
+    let mut path = get_db_path()?;
+    path.set_file_name("secrets.toml");
+    Ok(path)
+}
+
+fn use_fallback() -> bool {
+    env::var(STORE_VAR).is_ok_and(|store| store == "file")
+}
+
+// The keyring entry, or None when there's no keyring to use (no Secret
+// Service on a headless machine, say).
+fn keyring_entry(name: &str) -> Result<Option<Entry>> {
+    if use_fallback() {
+        return Ok(None);
+    }
+    let (service, account) = split_name(name)?;
+    let entry = Entry::new(service, account)?;
+
+    // Reading is the cheapest way to find out whether the keyring answers
+    match entry.get_password() {
+        Err(keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_)) => Ok(None),
+        _ => Ok(Some(entry)),
+    }
+}
+
+fn read_fallback() -> Result<toml::Table> {
+    let path = fallback_path()?;
+    if !path.exists() {
+        return Ok(toml::Table::new());
+    }
+    let content = fs::read_to_string(&path)?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn write_fallback(secrets: &toml::Table) -> Result<()> {
+    let path = fallback_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&path)?;
+
+    std::io::Write::write_all(&mut file, toml::to_string(secrets)?.as_bytes())?;
+    Ok(())
+}
+
+// The fallback file is keyed by the full service/account name
+fn fallback_key(name: &str) -> Result<String> {
+    let (service, account) = split_name(name)?;
+    Ok(format!("{}/{}", service, account))
+}
+
+fn get(name: &str) -> Result<String> {
+    if let Some(entry) = keyring_entry(name)? {
+        match entry.get_password() {
+            Ok(secret) => return Ok(secret),
+            Err(keyring::Error::NoEntry) => {}
+            Err(e) => return Err(e).context("Failed to read from the keyring"),
+        }
+    }
+
+    let secrets = read_fallback()?;
+    match secrets.get(&fallback_key(name)?) {
+        Some(toml::Value::String(secret)) => Ok(secret.clone()),
+        _ => anyhow::bail!("No secret named {} - store it with: estrange secret set {}", name, name),
+    }
+}
+
+// Store a secret, returning where it went
+pub fn set(name: &str, secret: &str) -> Result<String> {
+    if let Some(entry) = keyring_entry(name)? {
+        entry.set_password(secret).context("Failed to write to the keyring")?;
+        return Ok("the system keyring".to_string());
+    }
+
+    let mut secrets = read_fallback()?;
+    secrets.insert(fallback_key(name)?, toml::Value::String(secret.to_string()));
+    write_fallback(&secrets)?;
+    Ok(fallback_path()?.display().to_string())
+}
+
+// Remove a secret from wherever it's stored
+pub fn remove(name: &str) -> Result<()> {
+    let mut found = false;
+
+    if let Some(entry) = keyring_entry(name)? {
+        match entry.delete_credential() {
+            Ok(()) => found = true,
+            Err(keyring::Error::NoEntry) => {}
+            Err(e) => return Err(e).context("Failed to remove from the keyring"),
+        }
+    }
+
+    let mut secrets = read_fallback()?;
+    if secrets.remove(&fallback_key(name)?).is_some() {
+        write_fallback(&secrets)?;
+        found = true;
+    }
+
+    if !found {
+        anyhow::bail!("No secret named {}", name);
+    }
+    Ok(())
+}
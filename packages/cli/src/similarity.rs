@@ -0,0 +1,157 @@
+use anyhow::Result;
+use std::collections::HashSet;
+
+use crate::Database;
+
+// How alike two prompts must be, from 0 (nothing shared) to 1 (the same
+// words in the same order), before the newer one counts as a repeat.
+pub const DEFAULT_THRESHOLD: f64 = 0.5;
+// Times to ask again after a repeat before settling for the least similar
+// prompt received.
+pub const MAX_REGENERATIONS: usize = 3;
+// Recent prompts listed in the instruction as ones to avoid
+const AVOID_LIST_LENGTH: usize = 20;
+
+// Words that make prompts look alike without making them alike
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from", "in", "is", "it", "its", "of", "on",
+    "or", "that", "the", "their", "this", "to", "was", "what", "where", "which", "who", "with", "you", "your",
+];
+
+// The configured threshold, or the default.
+pub fn threshold(configured: Option<f64>) -> Result<f64> {
+    let threshold = configured.unwrap_or(DEFAULT_THRESHOLD);
+    if !(0.0..=1.0).contains(&threshold) {
+        anyhow::bail!("similarity_threshold must be between 0 and 1, got {}", threshold);
+    }
+    Ok(threshold)
+}
+
+// Lowercased words without punctuation, stopwords or a plural `s`.
+fn tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|word| word.trim_matches('\'').to_lowercase())
+        .filter(|word| !word.is_empty() && !STOPWORDS.contains(&word.as_str()))
+        .map(|word| match word.strip_suffix('s') {
+            Some(stem) if stem.len() > 2 && !stem.ends_with('s') => stem.to_string(),
+            _ => word,
+        })
+        .collect()
+}
+
+// Single words plus adjacent pairs, so shared phrasing counts for more than
+// a shared vocabulary.
+fn shingles(text: &str) -> HashSet<String> {
+    let tokens = tokens(text);
+    let pairs = tokens.windows(2).map(|pair| pair.join(" "));
+    tokens.iter().cloned().chain(pairs).collect()
+}
+
+// Jaccard similarity of the two prompts' shingles.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (shingles(a), shingles(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / a.union(&b).count() as f64
+}
+
+// The past prompt most like `prompt`, with its similarity.
+pub fn closest<'a>(prompt: &str, history: &'a [String]) -> Option<(f64, &'a str)> {
+    history
+        .iter()
+        .map(|past| (similarity(prompt, past), past.as_str()))
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+// The generator's instruction, followed by prompts the model shouldn't echo.
+pub fn with_avoid_list(instruction: &str, history: &[String], rejected: &[String]) -> String {
+    let avoid: Vec<&String> = rejected.iter().chain(history.iter().take(AVOID_LIST_LENGTH)).collect();
+    if avoid.is_empty() {
+        return instruction.to_string();
+    }
+
+    let list: String = avoid.iter().map(|prompt| format!("\n- {}", prompt)).collect();
+    format!(
+        "{}\n\nIt must be clearly different from each of these earlier prompts:{}",
+        instruction, list
+    )
+}
+
+impl Database {
+    // Every prompt this journal has received, newest first. Forgotten
+    // entries count too - a forgotten prompt is still one you've seen.
+    pub fn past_prompts(&self) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT prompt FROM creative_prompts WHERE prompt != '' ORDER BY datetime(created_at) DESC",
+        )?;
+        let prompts = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(prompts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_prompts_score_one() {
+        let prompt = "A doorknob that remembers every hand that's touched it";
+        assert_eq!(similarity(prompt, prompt), 1.0);
+    }
+
+    #[test]
+    fn near_duplicates_cross_the_threshold() {
+        let score = similarity(
+            "A doorknob that remembers every hand that's touched it",
+            "The doorknobs that remember every hand that touched them",
+        );
+        assert!(score >= DEFAULT_THRESHOLD, "scored {}", score);
+    }
+
+    #[test]
+    fn distinct_prompts_stay_below_it() {
+        let score = similarity(
+            "A doorknob that remembers every hand that's touched it",
+            "Gravity works sideways on Tuesdays",
+        );
+        assert_eq!(score, 0.0);
+
+        let score = similarity(
+            "A doorknob that remembers every hand that's touched it",
+            "A lighthouse that remembers every ship it failed to warn",
+        );
+        assert!(score > 0.0 && score < DEFAULT_THRESHOLD, "scored {}", score);
+    }
+
+    #[test]
+    fn stopwords_and_case_dont_count() {
+        assert_eq!(similarity("The Kettle", "a kettle"), 1.0);
+        assert_eq!(similarity("the and of", "kettle"), 0.0);
+        assert_eq!(similarity("", "kettle"), 0.0);
+    }
+
+    #[test]
+    fn closest_finds_the_most_similar_past_prompt() {
+        let history = vec![
+            "Gravity works sideways on Tuesdays".to_string(),
+            "A kettle that boils memories".to_string(),
+            "A lighthouse that keeps office hours".to_string(),
+        ];
+        let (score, past) = closest("The kettle boils old memories", &history).unwrap();
+        assert_eq!(past, "A kettle that boils memories");
+        assert!(score >= DEFAULT_THRESHOLD, "scored {}", score);
+
+        assert!(closest("anything", &[]).is_none());
+    }
+
+    #[test]
+    fn threshold_must_be_a_fraction() {
+        assert_eq!(threshold(None).unwrap(), DEFAULT_THRESHOLD);
+        assert_eq!(threshold(Some(0.8)).unwrap(), 0.8);
+        assert!(threshold(Some(1.5)).is_err());
+        assert!(threshold(Some(-0.1)).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, OptionalExtension};
+use serde_json::Value;
+
+use crate::{Database, IS_ANSWERED};
+
+// Tags live in `metadata.tags` as a sorted array of normalized strings.
+
+// SQL condition over `creative_prompts p` matching entries with a given tag.
+pub const HAS_TAG: &str =
+    "EXISTS (SELECT 1 FROM json_each(p.metadata, '$.tags') WHERE json_each.value = ?)";
+
+// Lowercase, hyphenated and at most 50 characters, e.g. "Deep Time" -> "deep-time".
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag
+        .trim()
+        .trim_start_matches('#')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase();
+
+    if tag.is_empty() {
+        None
+    } else {
+        Some(tag.chars().take(50).collect())
+    }
+}
+
+pub fn normalize_tags<S: AsRef<str>>(tags: &[S]) -> Vec<String> {
+    let mut normalized: Vec<String> = tags.iter().filter_map(|t| normalize_tag(t.as_ref())).collect();
+    normalized.sort();
+    normalized.dedup();
+    normalized
+}
+
+pub fn tags_from_metadata(metadata: Option<&str>) -> Vec<String> {
+    metadata
+        .and_then(|m| serde_json::from_str::<Value>(m).ok())
+        .and_then(|m| m.get("tags").cloned())
+        .and_then(|tags| serde_json::from_value(tags).ok())
+        .unwrap_or_default()
+}
+
+impl Database {
+    pub fn entry_tags(&self, id: i64) -> Result<Vec<String>> {
+        let metadata: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT metadata FROM creative_prompts WHERE id = ?1 AND deleted_at IS NULL",
+                [id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .with_context(|| format!("No entry with ID {}", id))?;
+
+        Ok(tags_from_metadata(metadata.as_deref()))
+    }
+
+    pub fn add_tags(&self, id: i64, tags: &[String]) -> Result<Vec<String>> {
+        let mut current = self.entry_tags(id)?;
+        current.extend(normalize_tags(tags));
+        self.set_tags(id, normalize_tags(&current))
+    }
+
+    pub fn remove_tags(&self, id: i64, tags: &[String]) -> Result<Vec<String>> {
+        let remove = normalize_tags(tags);
+        let current = self.entry_tags(id)?;
+        self.set_tags(id, current.into_iter().filter(|t| !remove.contains(t)).collect())
+    }
+
+    fn set_tags(&self, id: i64, tags: Vec<String>) -> Result<Vec<String>> {
+        let now = self.calendar.timestamp();
+
+        self.conn.execute(
+            "UPDATE creative_prompts
+             SET metadata = json_set(COALESCE(metadata, '{}'), '$.tags', json(?1)), modified_at = ?2
+             WHERE id = ?3",
+            params![serde_json::to_string(&tags)?, now, id],
+        )?;
+
+        Ok(tags)
+    }
+
+    // Every tag in the journal with the number of entries carrying it, most
+    // used first.
+    pub fn tag_counts(&self) -> Result<Vec<(String, i64)>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT json_each.value, COUNT(*) AS uses
+             FROM creative_prompts p, json_each(p.metadata, '$.tags')
+             WHERE p.deleted_at IS NULL AND {}
+             GROUP BY json_each.value
+             ORDER BY uses DESC, json_each.value ASC",
+            IS_ANSWERED
+        ))?;
+
+        let counts = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(counts)
+    }
+}
+
+pub fn format_tags(tags: &[String]) -> String {
+    tags.iter().map(|t| format!("#{}", t)).collect::<Vec<_>>().join(" ")
+}
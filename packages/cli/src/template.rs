@@ -0,0 +1,303 @@
+use anyhow::{Context, Result};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use crate::tags::normalize_tags;
+use crate::Database;
+
+// As in `git commit -v`: everything below this line is instructions, and
+// everything above it is the response exactly as written - `#` headings too.
+pub const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+// A response file: the writing area, holding `above` (a previous response,
+// or an empty front-matter block), then the scissors line and `notes`.
+pub fn compose(above: &str, notes: &[String]) -> String {
+    let mut content = String::new();
+    if !above.trim().is_empty() {
+        content.push_str(above.trim_end());
+        content.push('\n');
+    }
+    content.push_str("\n\n");
+    content.push_str(SCISSORS);
+    content.push_str("\n# Do not modify or remove the line above.\n# Everything below it is ignored.\n#\n");
+    for note in notes {
+        content.push_str(format!("# {}", note).trim_end());
+        content.push('\n');
+    }
+    content
+}
+
+// What was written. Files from before the scissors line (drafts, mostly)
+// had their instructions in `#` lines throughout, so those lose every `#` line.
+pub fn response_text(content: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    match lines.iter().position(|line| line.trim_end() == SCISSORS) {
+        Some(scissors) => lines[..scissors].join("\n").trim_end().trim_start_matches(['\n', '\r']).to_string(),
+        None => strip_comments(content),
+    }
+}
+
+// Remove comment lines and trim
+fn strip_comments(content: &str) -> String {
+    content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+// Words written in a response file. Front-matter fields count only once
+// filled in, so an untouched skeleton is no words at all; a block that can't
+// be read counts as written.
+pub fn word_count(content: &str) -> usize {
+    let text = response_text(content);
+    let words = |text: &str| text.split_whitespace().count();
+
+    let Some(format) = text.lines().next().and_then(FrontMatterFormat::from_delimiter) else {
+        return words(&text);
+    };
+    match split_front_matter(&text, format) {
+        Ok((front_matter, response)) => {
+            let fields = [front_matter.title, front_matter.mood].into_iter().flatten();
+            let fields: usize = fields.chain(front_matter.tags.unwrap_or_default()).map(|field| words(&field)).sum();
+            fields + words(&response)
+        }
+        Err(_) => words(&text),
+    }
+}
+
+// 1-based line the cursor should start on: below a legacy comment header
+// or a front-matter block and the blank line after it, else the top.
+pub fn first_writing_line(content: &str) -> usize {
+    let mut lines = content.lines();
+    if let Some(format) = lines.next().and_then(FrontMatterFormat::from_delimiter) {
+        if let Some(end) = lines.position(|line| line.trim_end() == format.delimiter()) {
+            return end + 4;
+        }
+    }
+    // A response above the scissors line may well open with a heading
+    if content.lines().any(|line| line.trim_end() == SCISSORS) {
+        return 1;
+    }
+
+    match content.lines().take_while(|line| line.trim_start().starts_with('#')).count() {
+        0 => 1,
+        header => header + 2,
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FrontMatterFormat {
+    // Between `---` lines
+    Yaml,
+    // Between `+++` lines
+    Toml,
+}
+
+impl FrontMatterFormat {
+    fn delimiter(self) -> &'static str {
+        match self {
+            FrontMatterFormat::Yaml => "---",
+            FrontMatterFormat::Toml => "+++",
+        }
+    }
+
+    fn from_delimiter(line: &str) -> Option<Self> {
+        match line.trim_end() {
+            "---" => Some(FrontMatterFormat::Yaml),
+            "+++" => Some(FrontMatterFormat::Toml),
+            _ => None,
+        }
+    }
+
+    // The empty block new responses start with
+    pub fn skeleton(self) -> String {
+        let fields = match self {
+            FrontMatterFormat::Yaml => "title:\nmood:\ntags: []",
+            FrontMatterFormat::Toml => "title = \"\"\nmood = \"\"\ntags = []",
+        };
+        format!("{0}\n{1}\n{0}", self.delimiter(), fields)
+    }
+}
+
+// The fields a response may open with. Others are ignored.
+#[derive(Deserialize, Default)]
+pub struct FrontMatter {
+    pub title: Option<String>,
+    pub mood: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+impl FrontMatter {
+    // Nothing filled in, as in a fresh skeleton
+    pub fn is_empty(&self) -> bool {
+        let blank = |field: &Option<String>| field.as_deref().is_none_or(|value| value.trim().is_empty());
+        blank(&self.title) && blank(&self.mood) && normalize_tags(self.tags.as_deref().unwrap_or_default()).is_empty()
+    }
+}
+
+// Split `text` into its front matter and the response after it. Text that
+// doesn't open with the format's delimiter has no front matter.
+pub fn split_front_matter(text: &str, format: FrontMatterFormat) -> Result<(FrontMatter, String)> {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim_end) != Some(format.delimiter()) {
+        return Ok((FrontMatter::default(), text.to_string()));
+    }
+
+    let rest: Vec<&str> = lines.collect();
+    let end = rest
+        .iter()
+        .position(|line| line.trim_end() == format.delimiter())
+        .with_context(|| format!("The front matter has no closing {} line", format.delimiter()))?;
+    let block = rest[..end].join("\n");
+
+    let front_matter: Option<FrontMatter> = match format {
+        FrontMatterFormat::Yaml => serde_yaml::from_str(&block).context("Couldn't read the YAML front matter")?,
+        FrontMatterFormat::Toml => Some(toml::from_str(&block).context("Couldn't read the TOML front matter")?),
+    };
+    let response = rest[end + 1..].join("\n").trim().to_string();
+    Ok((front_matter.unwrap_or_default(), response))
+}
+
+impl Database {
+    // Record a response's front matter in its entry's metadata. Tags join
+    // the entry's tags; blank fields are skipped.
+    pub fn apply_front_matter(&self, id: i64, front_matter: &FrontMatter) -> Result<()> {
+        for (key, value) in [("$.title", &front_matter.title), ("$.mood", &front_matter.mood)] {
+            if let Some(value) = value.as_deref().map(str::trim).filter(|value| !value.is_empty()) {
+                self.conn.execute(
+                    "UPDATE creative_prompts SET metadata = json_set(COALESCE(metadata, '{}'), ?1, ?2) WHERE id = ?3",
+                    params![key, value, id],
+                )?;
+            }
+        }
+
+        let tags = normalize_tags(front_matter.tags.as_deref().unwrap_or_default());
+        if !tags.is_empty() {
+            self.add_tags(id, &tags)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notes() -> Vec<String> {
+        vec!["Disruption: A kettle that boils memories".to_string(), String::new()]
+    }
+
+    #[test]
+    fn keeps_everything_above_the_scissors() {
+        let mut content = compose("", &notes());
+        content.insert_str(0, "# A heading I wrote\n\nThe kettle sang.\n");
+
+        assert_eq!(response_text(&content), "# A heading I wrote\n\nThe kettle sang.");
+    }
+
+    #[test]
+    fn empty_template_is_an_empty_response() {
+        assert_eq!(response_text(&compose("", &notes())), "");
+    }
+
+    #[test]
+    fn compose_drops_trailing_spaces_from_notes() {
+        let content = compose("", &notes());
+        assert!(content.lines().all(|line| line == line.trim_end()));
+        assert!(content.contains(SCISSORS));
+    }
+
+    #[test]
+    fn legacy_drafts_lose_their_comment_lines() {
+        let content = "# Creative Response Entry\n# Disruption: A kettle\n\nThe kettle sang.\n# a stray note\nThen it stopped.\n";
+        assert_eq!(response_text(content), "The kettle sang.\nThen it stopped.");
+    }
+
+    #[test]
+    fn cursor_starts_where_the_writing_goes() {
+        assert_eq!(first_writing_line(&compose("", &notes())), 1);
+        assert_eq!(first_writing_line("# one\n# two\n\n"), 4);
+
+        let skeleton = compose(&FrontMatterFormat::Yaml.skeleton(), &notes());
+        // Below the five-line block and the blank line after it
+        assert_eq!(first_writing_line(&skeleton), 7);
+    }
+
+    #[test]
+    fn yaml_front_matter_round_trips() {
+        let text = "---\ntitle: Kettle\nmood: wistful\ntags: [kitchen, deep time]\n---\n\nThe kettle sang.";
+        let (front_matter, response) = split_front_matter(text, FrontMatterFormat::Yaml).unwrap();
+
+        assert_eq!(front_matter.title.as_deref(), Some("Kettle"));
+        assert_eq!(front_matter.mood.as_deref(), Some("wistful"));
+        assert_eq!(front_matter.tags, Some(vec!["kitchen".to_string(), "deep time".to_string()]));
+        assert_eq!(response, "The kettle sang.");
+    }
+
+    #[test]
+    fn toml_front_matter_round_trips() {
+        let text = "+++\ntitle = \"Kettle\"\nmood = \"\"\ntags = []\n+++\nThe kettle sang.";
+        let (front_matter, response) = split_front_matter(text, FrontMatterFormat::Toml).unwrap();
+
+        assert_eq!(front_matter.title.as_deref(), Some("Kettle"));
+        assert_eq!(front_matter.mood.as_deref(), Some(""));
+        assert_eq!(response, "The kettle sang.");
+    }
+
+    #[test]
+    fn skeletons_parse_as_empty_front_matter() {
+        for format in [FrontMatterFormat::Yaml, FrontMatterFormat::Toml] {
+            let text = response_text(&compose(&format.skeleton(), &notes()));
+            let (front_matter, response) = split_front_matter(&text, format).unwrap();
+
+            assert!(front_matter.title.unwrap_or_default().is_empty());
+            assert!(front_matter.tags.unwrap_or_default().is_empty());
+            assert_eq!(response, "");
+        }
+    }
+
+    #[test]
+    fn filled_in_fields_make_front_matter_non_empty() {
+        let (skeleton, _) = split_front_matter(&FrontMatterFormat::Yaml.skeleton(), FrontMatterFormat::Yaml).unwrap();
+        assert!(skeleton.is_empty());
+
+        let (blank, _) = split_front_matter("---\ntitle: \"  \"\ntags: [\"\"]\n---\n", FrontMatterFormat::Yaml).unwrap();
+        assert!(blank.is_empty());
+
+        let (titled, _) = split_front_matter("---\ntitle: Kettle\n---\n", FrontMatterFormat::Yaml).unwrap();
+        assert!(!titled.is_empty());
+
+        let (tagged, _) = split_front_matter("+++\ntags = [\"kitchen\"]\n+++\n", FrontMatterFormat::Toml).unwrap();
+        assert!(!tagged.is_empty());
+    }
+
+    #[test]
+    fn text_without_front_matter_passes_through() {
+        let (front_matter, response) = split_front_matter("The kettle sang.", FrontMatterFormat::Yaml).unwrap();
+        assert!(front_matter.title.is_none());
+        assert_eq!(response, "The kettle sang.");
+    }
+
+    #[test]
+    fn unreadable_front_matter_is_an_error() {
+        assert!(split_front_matter("---\ntitle: Kettle\nThe kettle sang.", FrontMatterFormat::Yaml).is_err());
+        assert!(split_front_matter("---\ntags: [unclosed\n---\n", FrontMatterFormat::Yaml).is_err());
+        assert!(split_front_matter("+++\ntitle = \n+++\n", FrontMatterFormat::Toml).is_err());
+    }
+
+    #[test]
+    fn word_count_ignores_an_untouched_skeleton() {
+        for format in [FrontMatterFormat::Yaml, FrontMatterFormat::Toml] {
+            assert_eq!(word_count(&compose(&format.skeleton(), &notes())), 0);
+        }
+
+        let written = compose("---\ntitle: Kettle song\nmood:\ntags: []\n---\n\nThe kettle sang.", &notes());
+        assert_eq!(word_count(&written), 5);
+
+        assert_eq!(word_count(&compose("The kettle sang.", &notes())), 3);
+    }
+}
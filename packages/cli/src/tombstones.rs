@@ -0,0 +1,85 @@
+use anyhow::Result;
+use rusqlite::{params, OptionalExtension};
+
+use crate::Database;
+
+// How long a forgotten entry can be restored. After this its content is
+// erased and only the tombstone (sync_id + deleted_at) remains, so the
+// deletion still propagates to other devices through archives.
+pub const UNDO_WINDOW_DAYS: i64 = 30;
+
+impl Database {
+    pub fn forget_entry(&self, id: i64) -> Result<()> {
+        let now = self.calendar.timestamp();
+
+        let updated = self.conn.execute(
+            "UPDATE creative_prompts SET deleted_at = ?1, modified_at = ?1
+             WHERE id = ?2 AND deleted_at IS NULL",
+            params![now, id],
+        )?;
+
+        if updated == 0 {
+            anyhow::bail!("No entry with ID {}", id);
+        }
+        Ok(())
+    }
+
+    pub fn restore_entry(&self, id: i64) -> Result<()> {
+        let row: Option<(Option<String>, bool)> = self
+            .conn
+            .query_row("SELECT deleted_at, prompt = '' FROM creative_prompts WHERE id = ?1", [id], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .optional()?;
+
+        match row {
+            None => anyhow::bail!("No entry with ID {}", id),
+            Some((None, _)) => anyhow::bail!("Entry {} was never forgotten", id),
+            // Purged, or a tombstone imported for an entry this journal never had
+            Some((Some(_), true)) => {
+                anyhow::bail!("Entry {} can't be restored - only its tombstone is left in this journal", id)
+            }
+            Some((Some(_), false)) => {}
+        }
+
+        let now = self.calendar.timestamp();
+        let restored = self.conn.execute(
+            "UPDATE creative_prompts SET deleted_at = NULL, modified_at = ?1
+             WHERE id = ?2 AND datetime(deleted_at) > datetime('now', ?3)",
+            params![now, id, format!("-{} days", UNDO_WINDOW_DAYS)],
+        )?;
+
+        if restored == 0 {
+            anyhow::bail!(
+                "Entry {} was forgotten more than {} days ago and can no longer be restored",
+                id,
+                UNDO_WINDOW_DAYS
+            );
+        }
+        Ok(())
+    }
+
+    // Erase the content of entries forgotten longer ago than the undo window.
+    pub fn purge_expired_tombstones(&self) -> Result<()> {
+        let window = format!("-{} days", UNDO_WINDOW_DAYS);
+        let tx = self.conn.unchecked_transaction()?;
+
+        tx.execute(
+            "DELETE FROM entry_revisions WHERE entry_id IN (
+                 SELECT id FROM creative_prompts
+                 WHERE deleted_at IS NOT NULL AND datetime(deleted_at) <= datetime('now', ?1)
+             )",
+            [&window],
+        )?;
+        tx.execute(
+            "UPDATE creative_prompts
+             SET prompt = '', response = NULL, drawing_vector = NULL, drawing_preview = NULL, metadata = '{}'
+             WHERE deleted_at IS NOT NULL AND prompt != ''
+               AND datetime(deleted_at) <= datetime('now', ?1)",
+            [&window],
+        )?;
+
+        tx.commit()?;
+        Ok(())
+    }
+}